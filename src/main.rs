@@ -1,20 +1,54 @@
-use chrono::{DateTime, Days, Months, TimeDelta};
+use chrono::{
+    DateTime, Datelike, Days, FixedOffset, Months, NaiveDate, NaiveTime, TimeDelta, Timelike,
+    Weekday,
+};
+use rust_xlsxwriter::{
+    Chart, ChartFormat, ChartSolidFill, ChartType, ConditionalFormatCell,
+    ConditionalFormatCellRule, ExcelDateTime, Format, Workbook,
+};
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
 use serde_json::Value;
-use std::collections::HashMap;
+use sha2::{Digest, Sha256};
+use std::collections::{HashMap, HashSet};
 use std::fs::File;
-use std::io::Write;
-use std::string::String;
+use std::io::{IsTerminal, Read, Write};
 use std::option::Option;
+use std::path::{Path, PathBuf};
+use std::string::String;
 
-const API_MAX_TIME: Months = Months::new(3);
+const DEFAULT_MONTHS_SINCE_START: u32 = 3;
+const API_MAX_TIME_PER_REQUEST: Months = Months::new(3);
+const TOGGL_API_URL: &str = "https://api.track.toggl.com/api/v9";
+const TOGGL_API_VERSION: &str = "v9";
+const GOOGLE_SHEETS_API_URL: &str = "https://sheets.googleapis.com/v4";
+const NOTION_API_URL: &str = "https://api.notion.com/v1";
+const NOTION_API_VERSION: &str = "2022-06-28";
+const NOTION_MAX_BLOCK_CHILDREN: usize = 100;
+const AIRTABLE_API_URL: &str = "https://api.airtable.com/v0";
+const AIRTABLE_MAX_RECORDS_PER_REQUEST: usize = 10;
 const NORMAL_WORKING_TIME_PER_DAY_IN_SECONDS: i64 = 7 * 60 * 60;
+/// Two-tailed 95% critical values of the Student t-distribution, for 1 to 100
+/// degrees of freedom.
+const T_CRITICAL_VALUES_95: [f64; 100] = [
+    12.706, 4.303, 3.182, 2.776, 2.571, 2.447, 2.365, 2.306, 2.262, 2.228, 2.201, 2.179, 2.160,
+    2.145, 2.131, 2.120, 2.110, 2.101, 2.093, 2.086, 2.080, 2.074, 2.069, 2.064, 2.060, 2.056,
+    2.052, 2.048, 2.045, 2.042, 2.040, 2.037, 2.035, 2.032, 2.030, 2.028, 2.026, 2.024, 2.023,
+    2.021, 2.020, 2.018, 2.017, 2.015, 2.014, 2.013, 2.012, 2.011, 2.010, 2.009, 2.008, 2.007,
+    2.006, 2.005, 2.004, 2.003, 2.002, 2.002, 2.001, 2.000, 2.000, 1.999, 1.998, 1.998, 1.997,
+    1.997, 1.996, 1.995, 1.995, 1.994, 1.994, 1.993, 1.993, 1.993, 1.992, 1.992, 1.991, 1.991,
+    1.990, 1.990, 1.990, 1.989, 1.989, 1.989, 1.988, 1.988, 1.988, 1.987, 1.987, 1.987, 1.986,
+    1.986, 1.986, 1.986, 1.985, 1.985, 1.985, 1.984, 1.984, 1.984,
+];
+const CHART_HEIGHT: u16 = 15;
+const LARGE_NEGATIVE_BALANCE_IN_SECONDS: i64 = -NORMAL_WORKING_TIME_PER_DAY_IN_SECONDS;
 
 use structopt::StructOpt;
 
 #[derive(Debug, StructOpt)]
 #[structopt(
     name = "Shinken Extra Time",
-    about = "Calculate extra time worked at Shinken. On the period from J-3months to J-1day"
+    about = "Calculate extra time worked at Shinken. By default on the period from J-3months to J-1day, which --weeks/--months/--years-since-start, --year with --quarter/--month/--week, --ytd, --mtd, --wtd and --last-week/--last-month/--last-quarter change"
 )]
 struct Opt {
     /// Activate debug mode
@@ -32,20 +66,745 @@ struct Opt {
     /// Toggl API Token to use
     #[structopt(short, long)]
     token: Option<String>,
+
+    /// First day of work (YYYY-MM-DD), entries before this date are ignored
+    #[structopt(long)]
+    first_working_day: Option<NaiveDate>,
+
+    /// Last day of work (YYYY-MM-DD), entries after this date are ignored
+    #[structopt(long)]
+    last_working_day: Option<NaiveDate>,
+
+    /// Show the week with the lowest extra time
+    #[structopt(long)]
+    show_worst_week: bool,
+
+    /// Show the week with the highest extra time
+    #[structopt(long)]
+    show_best_week: bool,
+
+    /// Show the day with the most time worked
+    #[structopt(long)]
+    show_longest_day: bool,
+
+    /// Show the day with the least time worked
+    #[structopt(long)]
+    show_shortest_day: bool,
+
+    /// Print one line per day: date, worked, extra and cumulated extra time
+    #[structopt(long)]
+    compact: bool,
+
+    /// Print a detailed table with one row per day
+    #[structopt(long)]
+    wide: bool,
+
+    /// Do not print the total extra time worked line
+    #[structopt(long)]
+    no_summary: bool,
+
+    /// Locale used to format numbers (e.g. fr_FR), defaults to the system locale
+    /// for the terminal and to plain numbers for the CSV file
+    #[structopt(long)]
+    locale: Option<String>,
+
+    /// Show the average extra time worked per month
+    #[structopt(long)]
+    show_rate_per_month: bool,
+
+    /// Show the average extra time worked per week
+    #[structopt(long)]
+    show_rate_per_week: bool,
+
+    /// Print a special message when the balance is exactly zero
+    #[structopt(long)]
+    alert_on_zero: bool,
+
+    /// Exit with status code 2 when the balance is negative
+    #[structopt(long)]
+    negative_is_error: bool,
+
+    /// Exit with status code 3 when the balance is positive
+    #[structopt(long)]
+    positive_is_error: bool,
+
+    /// Exit with status code 3 when the balance exceeds this number of hours
+    #[structopt(long, allow_hyphen_values = true)]
+    max_balance_hours: Option<f64>,
+
+    /// Exit with status code 2 when the balance is below this number of hours
+    #[structopt(long, allow_hyphen_values = true)]
+    min_balance_hours: Option<f64>,
+
+    /// With --include-today, show the number of entries logged today
+    #[structopt(long, requires = "include-today")]
+    show_today_entry_count: bool,
+
+    /// Print durations in natural language, e.g. "5 hours and 30 minutes"
+    #[structopt(long)]
+    human_readable: bool,
+
+    /// Decorate the terminal output with emoji, ignored when NO_COLOR is set
+    #[structopt(long)]
+    emoji: bool,
+
+    /// String repeated to draw a rule between the rows of --compact and --wide
+    #[structopt(long, default_value = "", allow_hyphen_values = true)]
+    separator: String,
+
+    /// Do not add the daily and cumulated totals to the csv file
+    #[structopt(long)]
+    no_totals: bool,
+
+    /// Only write the date, total time worked and cumulated extra time of each day to the csv file
+    #[structopt(long, conflicts_with = "no-totals")]
+    totals_only: bool,
+
+    /// Add the Toggl id of each entry to the csv file
+    #[structopt(long)]
+    show_entry_ids: bool,
+
+    /// Add the description of each entry to the csv file
+    #[structopt(long)]
+    show_entry_descriptions: bool,
+
+    /// Add the project name of each entry to the csv file
+    #[structopt(long)]
+    show_entry_projects: bool,
+
+    /// Add the tags of each entry to the csv file
+    #[structopt(long)]
+    show_entry_tags: bool,
+
+    /// Do not write the duration of each entry to the csv file, only the daily totals
+    #[structopt(long)]
+    hide_tasks: bool,
+
+    /// Add a column summarising the whole period to the csv file
+    #[structopt(long)]
+    period_summary: bool,
+
+    /// Add "Start" and "End" columns describing the query to the csv file
+    #[structopt(long)]
+    start_end_columns: bool,
+
+    /// Add empty csv columns for the working days without any entry
+    #[structopt(long)]
+    pad_missing_days: bool,
+
+    /// Sort the csv columns by time worked, busiest day first, instead of by date
+    #[structopt(long)]
+    sort_columns_by_total_desc: bool,
+
+    /// Show the average time worked for each day of the week
+    #[structopt(long)]
+    group_by_weekday: bool,
+
+    /// Show how fast the cumulated extra time grows, using a linear regression
+    #[structopt(long)]
+    trend: bool,
+
+    /// Show the average extra time worked for each day of the week
+    #[structopt(long)]
+    seasonality: bool,
+
+    /// Show a rough burnout risk index, above 1.0 the extra time is both sustained and high
+    ///
+    /// The index is weeks_in_positive_streaks * average_daily_extra_hours /
+    /// --weekly-overtime-cap-hours, where weeks_in_positive_streaks counts the
+    /// weeks of runs of at least two weeks with positive extra time.
+    #[structopt(long)]
+    burnout_index: bool,
+
+    /// Weekly extra time, in hours, considered sustainable by --burnout-index
+    #[structopt(long, default_value = "5")]
+    weekly_overtime_cap_hours: f64,
+
+    /// Show the correlation between the day of the month and the extra time worked
+    #[structopt(long)]
+    correlation: bool,
+
+    /// Show a histogram of the daily extra time, with this number of bins
+    #[structopt(long)]
+    histogram: Option<usize>,
+
+    /// Show the 95% confidence interval of the average daily extra time
+    #[structopt(long)]
+    confidence_interval: bool,
+
+    /// Project the balance this number of working days ahead, using the --trend slope
+    #[structopt(long)]
+    forecast_days: Option<u32>,
+
+    /// Show the work sessions of each day
+    #[structopt(long)]
+    show_work_sessions: bool,
+
+    /// Longest gap, in minutes, between two entries of the same work session
+    #[structopt(long, default_value = "30")]
+    session_gap_minutes: i64,
+
+    /// Show statistics on the number of work sessions per day
+    #[structopt(long)]
+    session_count_stats: bool,
+
+    /// Show the total time between the work sessions of each day
+    #[structopt(long)]
+    show_break_duration: bool,
+
+    /// Show the lunch break of each day, the longest gap between 11:00 and 14:00
+    #[structopt(long)]
+    lunch_break_detection: bool,
+
+    /// Show statistics on the start time of the first entry of each day
+    #[structopt(long)]
+    show_start_times: bool,
+
+    /// Show statistics on the end time of the last entry of each day
+    #[structopt(long)]
+    show_end_times: bool,
+
+    /// Show how many days had extra time, missing time or exactly the expected time
+    #[structopt(long)]
+    overtime_days_count: bool,
+
+    /// Show the streaks of 3 or more consecutive working days with missing time
+    #[structopt(long)]
+    show_consecutive_deficit: bool,
+
+    /// Show the streaks of 3 or more consecutive working days with extra time
+    #[structopt(long)]
+    show_consecutive_surplus: bool,
+
+    /// Draw the cumulated extra time as a chart
+    #[structopt(long)]
+    cumulative_chart: bool,
+
+    /// Draw the daily extra time above the cumulated extra time
+    #[structopt(long)]
+    dual_chart: bool,
+
+    /// Browse the daily extra time in an interactive chart, press 'q' to quit
+    #[structopt(long, conflicts_with = "ndjson")]
+    interactive_chart: bool,
+
+    /// Generate json file
+    #[structopt(long)]
+    json: bool,
+
+    /// Indent the json file
+    #[structopt(long)]
+    json_pretty: bool,
+
+    /// Number of spaces used to indent the json file with --json-pretty
+    #[structopt(long, default_value = "2")]
+    json_indent: usize,
+
+    /// Write the json file as a flat array of days
+    #[structopt(long)]
+    json_flat: bool,
+
+    /// Print one json object per day to stdout, instead of the usual output
+    #[structopt(long)]
+    ndjson: bool,
+
+    /// Print the JSON schema of the json file and exit
+    #[structopt(long)]
+    schema: bool,
+
+    /// Validate the json file against this JSON schema file
+    #[structopt(long, requires = "json", parse(from_os_str))]
+    validate_output: Option<PathBuf>,
+
+    /// Character separating the cells of the csv file
+    #[structopt(long, default_value = ";")]
+    delimiter: char,
+
+    /// Name of the csv file to write, defaults to results.csv
+    #[structopt(long)]
+    output: Option<String>,
+
+    /// Generate a tab separated file, written to results.tsv unless --output is set
+    #[structopt(long, conflicts_with = "delimiter")]
+    tsv: bool,
+
+    /// Generate a pipe separated file, written to results.psv unless --output is set
+    #[structopt(long, conflicts_with_all = &["delimiter", "tsv"])]
+    psv: bool,
+
+    /// Generate an Excel file, results.xlsx
+    #[structopt(long)]
+    excel: bool,
+
+    /// Embed a bar chart of the daily extra time in the Excel file
+    #[structopt(long, requires = "excel")]
+    excel_chart: bool,
+
+    /// Upload the report to a Google Sheets spreadsheet
+    #[structopt(long, requires_all = &["sheet-id", "gcp-credential-file"])]
+    google_sheets: bool,
+
+    /// Id of the Google Sheets spreadsheet to upload the report to
+    #[structopt(long)]
+    sheet_id: Option<String>,
+
+    /// Google service account key file used to upload the report
+    #[structopt(long, parse(from_os_str))]
+    gcp_credential_file: Option<PathBuf>,
+
+    /// Create a Notion page with the report
+    #[structopt(long, requires_all = &["notion-token", "parent-page"])]
+    notion_page: bool,
+
+    /// Notion integration token used to create the page
+    #[structopt(long)]
+    notion_token: Option<String>,
+
+    /// Id of the Notion page under which the report page is created
+    #[structopt(long)]
+    parent_page: Option<String>,
+
+    /// Sync one record per day to an Airtable table
+    #[structopt(long, requires_all = &["base-id", "table", "airtable-token"])]
+    airtable: bool,
+
+    /// Id of the Airtable base to sync the report to
+    #[structopt(long)]
+    base_id: Option<String>,
+
+    /// Name of the Airtable table to sync the report to
+    #[structopt(long)]
+    table: Option<String>,
+
+    /// Airtable personal access token
+    #[structopt(long)]
+    airtable_token: Option<String>,
+
+    /// Quoting and line ending rules of the csv file
+    #[structopt(long, possible_values = &["excel", "unix", "rfc4180"])]
+    csv_dialect: Option<CsvDialect>,
+
+    /// Line ending of the csv file, overriding the one of --csv-dialect
+    #[structopt(long, possible_values = &["crlf", "lf"])]
+    line_ending: Option<LineEnding>,
+
+    /// Start the csv file with a UTF-8 byte order mark, for Excel
+    #[structopt(long)]
+    bom: bool,
+
+    /// Expect no work on weekends and report the weekend overtime separately
+    #[structopt(long)]
+    include_weekends: bool,
+
+    /// Hourly rate used to value the extra time worked
+    #[structopt(long, allow_hyphen_values = true)]
+    hourly_rate: Option<f64>,
+
+    /// Factor applied to the hourly rate for weekend overtime, with --include-weekends
+    #[structopt(long, requires_all = &["hourly-rate", "include-weekends"])]
+    weekend_rate_multiplier: Option<f64>,
+
+    /// Rank the days from the most to the least extra time
+    #[structopt(long)]
+    show_daily_rank: bool,
+
+    /// Write every entry of the period to this csv file
+    #[structopt(long, parse(from_os_str))]
+    export_entries: Option<PathBuf>,
+
+    /// Template of the lines of --export-entries, e.g. "{start} | {duration} | {project}"
+    #[structopt(long, requires = "export-entries")]
+    entry_format: Option<String>,
+
+    /// UTC offset (+HH:MM) used to split the entries into days
+    #[structopt(long, allow_hyphen_values = true, parse(try_from_str = parse_timezone_offset))]
+    timezone_offset: Option<FixedOffset>,
+
+    /// Fail when the time zone of the system, used by default, can not be detected
+    #[structopt(long, conflicts_with = "timezone-offset")]
+    detect_timezone: bool,
+
+    /// Time zone (e.g. Europe/Paris) used to split the entries into days
+    #[structopt(long, conflicts_with_all = &["timezone-offset", "detect-timezone"])]
+    time_zone: Option<chrono_tz::Tz>,
+
+    /// Time zone in which the times of the entries are shown, if not the one of the days
+    #[structopt(long)]
+    entry_timezone: Option<chrono_tz::Tz>,
+
+    /// Start of the workday window (HH:MM), time worked before it is not counted
+    #[structopt(long, requires = "end-at")]
+    start_at: Option<NaiveTime>,
+
+    /// End of the workday window (HH:MM), time worked after it is not counted
+    #[structopt(long, requires = "start-at")]
+    end_at: Option<NaiveTime>,
+
+    /// Skip the entries started exactly at midnight
+    #[structopt(long)]
+    ignore_midnight_entries: bool,
+
+    /// Print the Toggl API quota left after each request
+    #[structopt(long)]
+    show_api_quota: bool,
+
+    /// Print the number of requests sent to the Toggl API
+    #[structopt(long)]
+    api_call_count: bool,
+
+    /// Read the time entries from this JSON file instead of the Toggl API
+    #[structopt(long, parse(from_os_str))]
+    mock_api: Option<PathBuf>,
+
+    /// Print the size of each answer of the Toggl API
+    #[structopt(long)]
+    show_api_response_size: bool,
+
+    /// Ask the Toggl API for gzip-compressed answers
+    #[structopt(long)]
+    compress_response: bool,
+
+    /// Print the Toggl API query URLs without sending the requests
+    #[structopt(long)]
+    print_query_url: bool,
+
+    /// Print to stderr the curl command matching each Toggl API request
+    #[structopt(long)]
+    curl_equivalent: bool,
+
+    /// Comma separated fields of the days to keep in the json file
+    #[structopt(long, requires = "json")]
+    fields: Option<String>,
+
+    /// Comma separated old=new renames of the fields of the json file
+    #[structopt(long, requires = "json")]
+    rename_fields: Option<String>,
+
+    /// Add the tool version, generation time and query to the json file
+    #[structopt(long, requires = "json", conflicts_with = "json-flat")]
+    add_metadata: bool,
+
+    /// Add a SHA-256 checksum of the report to the json file
+    #[structopt(long, requires = "json", conflicts_with = "json-flat")]
+    checksum: bool,
+
+    /// Check the checksum of a json file written with --checksum and exit
+    #[structopt(long, parse(from_os_str))]
+    verify_checksum: Option<PathBuf>,
+
+    /// Warn about days where the same duration was logged several times
+    #[structopt(long)]
+    check_duplicates: bool,
+
+    /// Print the time between the first and last entry of each day that was not logged
+    #[structopt(long)]
+    show_entry_gaps: bool,
+
+    /// Only look at the last N weeks
+    #[structopt(long)]
+    weeks_since_start: Option<u64>,
+
+    /// Only look at the last N months, 3 by default
+    #[structopt(long, conflicts_with = "weeks-since-start")]
+    months_since_start: Option<u32>,
+
+    /// Only look at the last N years, fetched with several requests
+    #[structopt(long, conflicts_with_all = &["weeks-since-start", "months-since-start"])]
+    years_since_start: Option<u32>,
+
+    /// Only look at this quarter (1 to 4) of --year
+    #[structopt(
+        long,
+        requires = "year",
+        conflicts_with_all = &["weeks-since-start", "months-since-start", "years-since-start"]
+    )]
+    quarter: Option<u8>,
+
+    /// Only look at this ISO week of --year
+    #[structopt(
+        long,
+        requires = "year",
+        conflicts_with_all = &["quarter", "weeks-since-start", "months-since-start", "years-since-start"]
+    )]
+    week: Option<u32>,
+
+    /// Only look at this month of --year, as a number (3) or a name (march)
+    #[structopt(
+        long,
+        requires = "year",
+        parse(try_from_str = parse_month),
+        conflicts_with_all = &["quarter", "week", "weeks-since-start", "months-since-start", "years-since-start"]
+    )]
+    month: Option<u32>,
+
+    /// Year of the period to look at
+    #[structopt(long)]
+    year: Option<i32>,
+
+    /// Look at the current year up to yesterday (today with --include-today)
+    #[structopt(
+        long,
+        conflicts_with_all = &["year", "weeks-since-start", "months-since-start", "years-since-start"]
+    )]
+    ytd: bool,
+
+    /// Look at the current month up to yesterday (today with --include-today)
+    #[structopt(
+        long,
+        conflicts_with_all = &["ytd", "year", "weeks-since-start", "months-since-start", "years-since-start"]
+    )]
+    mtd: bool,
+
+    /// Look at the current week up to yesterday (today with --include-today)
+    #[structopt(
+        long,
+        conflicts_with_all = &["ytd", "mtd", "year", "weeks-since-start", "months-since-start", "years-since-start"]
+    )]
+    wtd: bool,
+
+    /// Look at the whole previous week
+    #[structopt(
+        long,
+        conflicts_with_all = &["ytd", "mtd", "wtd", "year", "weeks-since-start", "months-since-start", "years-since-start"]
+    )]
+    last_week: bool,
+
+    /// Look at the whole previous month
+    #[structopt(
+        long,
+        conflicts_with_all = &["ytd", "mtd", "wtd", "last-week", "year", "weeks-since-start", "months-since-start", "years-since-start"]
+    )]
+    last_month: bool,
+
+    /// Look at the whole previous quarter, following --fiscal-year-start
+    #[structopt(
+        long,
+        conflicts_with_all = &["ytd", "mtd", "wtd", "last-week", "last-month", "year", "weeks-since-start", "months-since-start", "years-since-start"]
+    )]
+    last_quarter: bool,
+
+    /// First day of the week, e.g. monday or sunday
+    #[structopt(long, default_value = "monday")]
+    week_start: Weekday,
+
+    /// Month (1 to 12) starting the fiscal year, January by default
+    #[structopt(long)]
+    fiscal_year_start: Option<u8>,
+}
+
+/// Parses a month given as its number or its English name, in any case.
+fn parse_month(month: &str) -> Result<u32, String> {
+    const MONTH_NAMES: [&str; 12] = [
+        "january",
+        "february",
+        "march",
+        "april",
+        "may",
+        "june",
+        "july",
+        "august",
+        "september",
+        "october",
+        "november",
+        "december",
+    ];
+    let number = match month.parse::<u32>() {
+        Ok(number) => number,
+        Err(_) => MONTH_NAMES
+            .iter()
+            .position(|name| name.eq_ignore_ascii_case(month))
+            .map(|index| index as u32 + 1)
+            .unwrap_or(0),
+    };
+    if (1..=12).contains(&number) {
+        Ok(number)
+    } else {
+        Err(format!(
+            "Invalid month {}, expected 1 to 12 or a month name",
+            month
+        ))
+    }
+}
+
+/// Parses a UTC offset written as +HH:MM or -HH:MM.
+fn parse_timezone_offset(offset: &str) -> Result<FixedOffset, String> {
+    let invalid = || format!("Invalid offset {}, expected +HH:MM or -HH:MM", offset);
+    let sign = match offset.chars().next() {
+        Some('+') => 1,
+        Some('-') => -1,
+        _ => return Err(invalid()),
+    };
+    let (hours, minutes) = offset[1..].split_once(':').ok_or_else(invalid)?;
+    // Only digits, `parse` would accept a sign in either part
+    let number = |digits: &str| {
+        if digits.is_empty() || !digits.chars().all(|c| c.is_ascii_digit()) {
+            return Err(invalid());
+        }
+        digits.parse::<i32>().map_err(|_| invalid())
+    };
+    let (hours, minutes) = (number(hours)?, number(minutes)?);
+    if minutes >= 60 {
+        return Err(invalid());
+    }
+    FixedOffset::east_opt(sign * (hours * 3600 + minutes * 60)).ok_or_else(invalid)
+}
+
+#[derive(Debug, Clone)]
+struct Locale {
+    thousands_separator: Option<char>,
+    decimal_separator: char,
+}
+
+impl Locale {
+    /// Builds a locale from a name such as `fr_FR`, `de-DE` or `en_US.UTF-8`.
+    /// Unknown names, `C` and `POSIX` give plain numbers without grouping.
+    fn from_name(name: &str) -> Locale {
+        let language = name
+            .split(['_', '-', '.', '@'])
+            .next()
+            .unwrap_or("")
+            .to_lowercase();
+        let (thousands_separator, decimal_separator) = match language.as_str() {
+            "en" | "ja" | "zh" | "ko" | "he" | "th" => (Some(','), '.'),
+            "fr" | "ru" | "pl" | "cs" | "sk" | "sv" | "nb" | "nn" | "fi" | "uk" | "hu" => {
+                (Some(' '), ',')
+            }
+            "de" | "es" | "it" | "pt" | "nl" | "da" | "tr" | "id" | "el" | "ro" => (Some('.'), ','),
+            _ => (None, '.'),
+        };
+        Locale {
+            thousands_separator,
+            decimal_separator,
+        }
+    }
+
+    fn system() -> Locale {
+        Locale::from_name(&sys_locale::get_locale().unwrap_or_default())
+    }
+
+    fn group_digits(&self, digits: &str) -> String {
+        match self.thousands_separator {
+            Some(separator) => {
+                let mut grouped = String::new();
+                for (i, digit) in digits.chars().enumerate() {
+                    if i > 0 && (digits.len() - i).is_multiple_of(3) {
+                        grouped.push(separator);
+                    }
+                    grouped.push(digit);
+                }
+                grouped
+            }
+            None => digits.to_string(),
+        }
+    }
+
+    fn format_integer(&self, value: i64) -> String {
+        let sign = if value < 0 { "-" } else { "" };
+        format!(
+            "{}{}",
+            sign,
+            self.group_digits(&value.unsigned_abs().to_string())
+        )
+    }
+
+    fn format_decimal(&self, value: f64, precision: usize) -> String {
+        let formatted = format!("{:.*}", precision, value.abs());
+        let (integer_part, decimal_part) = match formatted.split_once('.') {
+            Some((integer_part, decimal_part)) => (integer_part, Some(decimal_part)),
+            None => (formatted.as_str(), None),
+        };
+        let sign = if value < 0.0 && formatted.chars().any(|c| c != '0' && c != '.') {
+            "-"
+        } else {
+            ""
+        };
+        match decimal_part {
+            Some(decimal_part) => format!(
+                "{}{}{}{}",
+                sign,
+                self.group_digits(integer_part),
+                self.decimal_separator,
+                decimal_part
+            ),
+            None => format!("{}{}", sign, self.group_digits(integer_part)),
+        }
+    }
+}
+
+/// Quoting and line ending rules of a csv file.
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum CsvDialect {
+    /// CRLF line endings, every non numeric cell quoted
+    Excel,
+    /// LF line endings, cells quoted only when needed
+    Unix,
+    /// CRLF line endings, cells quoted only when needed, as in RFC 4180
+    Rfc4180,
+}
+
+impl std::str::FromStr for CsvDialect {
+    type Err = String;
+
+    fn from_str(name: &str) -> Result<CsvDialect, String> {
+        match name {
+            "excel" => Ok(CsvDialect::Excel),
+            "unix" => Ok(CsvDialect::Unix),
+            "rfc4180" => Ok(CsvDialect::Rfc4180),
+            _ => Err(format!("Unknown csv dialect {}", name)),
+        }
+    }
+}
+
+/// Characters ending each line of a csv file.
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum LineEnding {
+    Crlf,
+    Lf,
+}
+
+impl LineEnding {
+    fn as_str(&self) -> &'static str {
+        match self {
+            LineEnding::Crlf => "\r\n",
+            LineEnding::Lf => "\n",
+        }
+    }
+}
+
+impl std::str::FromStr for LineEnding {
+    type Err = String;
+
+    fn from_str(name: &str) -> Result<LineEnding, String> {
+        match name {
+            "crlf" => Ok(LineEnding::Crlf),
+            "lf" => Ok(LineEnding::Lf),
+            _ => Err(format!("Unknown line ending {}", name)),
+        }
+    }
 }
 
 struct CSVSheet {
     columns: Vec<Vec<String>>,
     max_columns_length: usize,
     file_name: String,
+    delimiter: char,
+    dialect: Option<CsvDialect>,
+    line_ending: Option<LineEnding>,
+    bom: bool,
+    locale: Locale,
 }
 
 impl CSVSheet {
-    fn new(file_name: &str) -> CSVSheet {
+    fn new(file_name: &str, delimiter: char, locale: Locale) -> CSVSheet {
         CSVSheet {
             columns: Vec::new(),
             max_columns_length: 0,
             file_name: file_name.to_string(),
+            delimiter,
+            dialect: None,
+            line_ending: None,
+            bom: false,
+            locale,
         }
     }
     fn add_column(&mut self, column: Vec<String>) {
@@ -56,6 +815,19 @@ impl CSVSheet {
         self.columns.sort_by(|a, b| a[0].cmp(&b[0]));
     }
 
+    fn sort_columns_by_total_desc(
+        &mut self,
+        work_duration_in_seconds_per_day: &HashMap<String, i64>,
+    ) {
+        self.columns.sort_by_key(|column| {
+            std::cmp::Reverse(
+                *work_duration_in_seconds_per_day
+                    .get(&column[0])
+                    .unwrap_or(&0),
+            )
+        });
+    }
+
     fn update_max_columns_length(&mut self) {
         for column in self.columns.iter_mut() {
             if column.len() > self.max_columns_length {
@@ -66,7 +838,7 @@ impl CSVSheet {
 
     fn align_columns(&mut self) {
         self.update_max_columns_length();
-        for mut column in self.columns.iter_mut() {
+        for column in self.columns.iter_mut() {
             let len_difference = self.max_columns_length - column.len();
             for _ in 0..len_difference {
                 column.push(String::from(""));
@@ -77,168 +849,3785 @@ impl CSVSheet {
     fn add_total_times_to_columns(
         &mut self,
         work_duration_in_seconds_per_day: &HashMap<String, i64>,
-        cumulated_extra_time_per_day: &HashMap<String, i64>
+        extra_time_per_day: &HashMap<String, i64>,
+        cumulated_extra_time_per_day: &HashMap<String, i64>,
     ) {
         self.align_columns();
-        for mut column in self.columns.iter_mut() {
+        for column in self.columns.iter_mut() {
             let column_day = column[0].clone();
             let total_work_at_day = work_duration_in_seconds_per_day.get(&column_day).unwrap();
             column.push(String::from(""));
             column.push(String::from("Total time worked that day :"));
-            column.push(total_work_at_day.to_string());
+            column.push(self.locale.format_integer(*total_work_at_day));
 
             column.push(String::from(""));
             column.push(String::from("Extra time worked that day :"));
-            let extra_time_worked_at_day =
-                total_work_at_day - NORMAL_WORKING_TIME_PER_DAY_IN_SECONDS;
-            column.push(extra_time_worked_at_day.to_string());
+            column.push(
+                self.locale
+                    .format_integer(*extra_time_per_day.get(&column_day).unwrap()),
+            );
 
             column.push(String::from(""));
             column.push(String::from("Cumulated extra time worked :"));
-            column.push(cumulated_extra_time_per_day.get(&column_day).unwrap().to_string())
+            column.push(
+                self.locale
+                    .format_integer(*cumulated_extra_time_per_day.get(&column_day).unwrap()),
+            )
         }
         self.update_max_columns_length();
     }
 
-    fn write_csv_file(&self) {
-        let mut file = File::create(&self.file_name).expect("Could not create CSV file");
-        for cell in 0..self.max_columns_length {
-            for column in self.columns.iter() {
-                write!(&mut file, "{};", column[cell]).expect("Could not write to CSV file");
+    /// Appends the rank of the day to the columns of the days in `ranks`.
+    fn add_rank_to_columns(&mut self, ranks: &HashMap<String, usize>) {
+        self.align_columns();
+        for column in self.columns.iter_mut() {
+            match ranks.get(&column[0]) {
+                Some(rank) => {
+                    column.push(String::from(""));
+                    column.push(String::from("Rank :"));
+                    column.push(rank.to_string());
+                }
+                None => column.extend([String::new(), String::new(), String::new()]),
             }
-            write!(&mut file, "\n").expect("Could not write to CSV file");
         }
+        self.update_max_columns_length();
     }
-}
 
-fn main() {
-    let opt = Opt::from_args();
+    /// Appends "(weekend)" to the title of the columns of Saturdays and Sundays.
+    fn label_weekend_columns(&mut self) {
+        for column in self.columns.iter_mut() {
+            if let Ok(date) = NaiveDate::parse_from_str(&column[0], "%Y-%m-%d")
+                && !is_working_day(date)
+            {
+                column[0] = format!("{} (weekend)", column[0]);
+            }
+        }
+    }
 
-    if let Some(token) = opt.token {
-        let token = token.as_str();
-        let debug = opt.debug;
-        let include_today = opt.include_today;
-        let mut sheet = CSVSheet::new("results.csv");
-
-        let current_time = chrono::offset::Local::now();
-        let query_start = current_time
-            .checked_sub_months(API_MAX_TIME)
-            .unwrap()
-            .date_naive()
-            .format("%Y-%m-%d")
-            .to_string();
-        let mut query_end = String::new();
-        if include_today {
-            query_end = current_time.date_naive().format("%Y-%m-%d").to_string();
+    /// Adds a column made of a title followed by label and value cells, as the
+    /// first column when `prepend` is set and as the last one otherwise.
+    fn add_metadata_column(&mut self, title: &str, metadata: Vec<(String, String)>, prepend: bool) {
+        let mut column = Vec::from([title.to_string()]);
+        for (label, value) in metadata {
+            column.push(label);
+            column.push(value);
+        }
+        if prepend {
+            self.columns.insert(0, column);
         } else {
-            query_end = current_time
-                .checked_sub_days(Days::new(1))
-                .unwrap()
-                .date_naive()
-                .format("%Y-%m-%d")
-                .to_string();
+            self.columns.push(column);
         }
+        self.align_columns();
+    }
 
-        let mut total_work_duration_per_day: HashMap<String, i64> = HashMap::new();
-        let mut all_days = Vec::new();
+    /// Quotes a cell as described in RFC 4180 when it contains the delimiter,
+    /// a double quote or a line break.
+    fn escape_cell(&self, cell: &str) -> String {
+        let quote_strings = self.dialect == Some(CsvDialect::Excel)
+            && !cell.is_empty()
+            && cell.parse::<f64>().is_err();
+        if quote_strings || cell.contains([self.delimiter, '"', '\n', '\r']) {
+            format!("\"{}\"", cell.replace('"', "\"\""))
+        } else {
+            cell.to_string()
+        }
+    }
 
-        println!(
-            "Computing extra time worked between {} and {}",
-            query_start, query_end
-        );
+    /// Writes the sheet row by row. Without a dialect each cell is followed by
+    /// the delimiter, a dialect only puts it between cells. An explicit line
+    /// ending takes precedence over the one of the dialect.
+    fn write_to<W: Write>(&self, writer: &mut W) -> std::io::Result<()> {
+        let line_ending = match (self.line_ending, self.dialect) {
+            (Some(line_ending), _) => line_ending,
+            (None, Some(CsvDialect::Excel)) | (None, Some(CsvDialect::Rfc4180)) => LineEnding::Crlf,
+            (None, Some(CsvDialect::Unix)) | (None, None) => LineEnding::Lf,
+        }
+        .as_str();
+        if self.bom {
+            write!(writer, "\u{feff}")?;
+        }
+        for cell in 0..self.max_columns_length {
+            let cells: Vec<String> = self
+                .columns
+                .iter()
+                .map(|column| self.escape_cell(&column[cell]))
+                .collect();
+            let mut row = cells.join(&self.delimiter.to_string());
+            if self.dialect.is_none() && !cells.is_empty() {
+                row.push(self.delimiter);
+            }
+            write!(writer, "{}{}", row, line_ending)?;
+        }
+        Ok(())
+    }
 
-        let url_to_query = format!(
-            "https://api.track.toggl.com/api/v9/me/time_entries?start_date={}&end_date={}",
-            query_start, query_end
-        );
+    fn write_csv_file(&self) {
+        let mut file = File::create(&self.file_name).expect("Could not create CSV file");
+        self.write_to(&mut file)
+            .expect("Could not write to CSV file");
+    }
+}
 
-        println!("Querying url: {}", url_to_query);
+/// Appends a "Period Summary" column to the sheet. Its title sorts after the
+/// dates, so it stays the last column when `sort_columns` is called.
+fn add_period_summary_column(
+    sheet: &mut CSVSheet,
+    all_days: &[String],
+    work_duration_in_seconds_per_day: &HashMap<String, i64>,
+    extra_time_per_day: &HashMap<String, i64>,
+) {
+    let working_days = all_days.len() as i64;
+    let total_worked: i64 = all_days
+        .iter()
+        .map(|day| work_duration_in_seconds_per_day.get(day).unwrap())
+        .sum();
+    let total_extra: i64 = all_days
+        .iter()
+        .map(|day| extra_time_per_day.get(day).unwrap())
+        .sum();
+    let total_expected = total_worked - total_extra;
+    let average_daily_extra = if working_days > 0 {
+        total_extra / working_days
+    } else {
+        0
+    };
+    let locale = sheet.locale.clone();
+    sheet.add_column(Vec::from([
+        String::from("Period Summary"),
+        String::from("Working days :"),
+        locale.format_integer(working_days),
+        String::from("Total time worked :"),
+        locale.format_integer(total_worked),
+        String::from("Total expected time :"),
+        locale.format_integer(total_expected),
+        String::from("Total extra time :"),
+        locale.format_integer(total_extra),
+        String::from("Average daily extra time :"),
+        locale.format_integer(average_daily_extra),
+    ]));
+    sheet.align_columns();
+}
 
-        let client = reqwest::blocking::Client::new();
-        let resp_text = client
-            .get(url_to_query)
-            .basic_auth(token, Some("api_token"))
-            .send()
-            .unwrap()
-            .text()
-            .unwrap();
+/// Fields of a day that `--fields` can select
+const DAILY_ENTRY_FIELDS: [&str; 5] = ["date", "worked", "expected", "extra", "cumulative"];
 
-        let all_tasks: Vec<Value> = serde_json::from_str(&resp_text).unwrap();
+/// Time worked on a day, in seconds
+#[derive(Debug, Clone, Serialize, JsonSchema)]
+struct DailyEntry {
+    /// Day, as YYYY-MM-DD
+    date: String,
+    /// Time worked that day
+    worked: i64,
+    /// Time expected to be worked that day
+    expected: i64,
+    /// Extra time worked that day
+    extra: i64,
+    /// Extra time worked since the start of the period, that day included
+    cumulative: i64,
+}
 
-        let mut tasks_per_day: HashMap<String, Vec<i64>> = HashMap::new();
+/// Extra time worked over a period, in seconds
+#[derive(Debug, Clone, Serialize, JsonSchema)]
+struct ExtraTimeReport {
+    /// First day of the period, as YYYY-MM-DD
+    query_start: String,
+    /// Last day of the period, as YYYY-MM-DD
+    query_end: String,
+    /// Days of the period with at least one entry
+    days: Vec<DailyEntry>,
+    /// Extra time worked over the whole period
+    total: i64,
+    /// Extra time worked on weekdays
+    weekday_extra_seconds: i64,
+    /// Extra time worked on weekends
+    weekend_extra_seconds: i64,
+    /// How and when the report was generated, with --add-metadata
+    #[serde(skip_serializing_if = "Option::is_none")]
+    metadata: Option<ReportMetadata>,
+}
 
-        for task in all_tasks.into_iter() {
-            let day_as_string = DateTime::parse_from_rfc3339(&task["start"].as_str().unwrap())
-                .unwrap()
-                .date_naive()
-                .format("%Y-%m-%d")
-                .to_string();
+/// Describes the run that generated a report
+#[derive(Debug, Clone, Serialize, JsonSchema)]
+struct ReportMetadata {
+    /// Version of calculate_extra_time
+    tool_version: String,
+    /// Generation time, as RFC 3339
+    generated_at: String,
+    /// First day queried, as YYYY-MM-DD
+    query_start: String,
+    /// Last day queried, as YYYY-MM-DD
+    query_end: String,
+    /// Hours expected on a working day
+    working_hours_per_day: f64,
+    /// Version of the Toggl API queried
+    toggl_api_version: String,
+}
 
-            let worktime_in_seconds = task["duration"].as_i64().unwrap();
+impl ReportMetadata {
+    fn new(query_start: NaiveDate, query_end: NaiveDate) -> ReportMetadata {
+        ReportMetadata {
+            tool_version: env!("CARGO_PKG_VERSION").to_string(),
+            generated_at: chrono::offset::Local::now().to_rfc3339(),
+            query_start: query_start.to_string(),
+            query_end: query_end.to_string(),
+            working_hours_per_day: NORMAL_WORKING_TIME_PER_DAY_IN_SECONDS as f64 / 3600.0,
+            toggl_api_version: TOGGL_API_VERSION.to_string(),
+        }
+    }
+}
 
-            if tasks_per_day.contains_key(&day_as_string) {
-                let mut current_tasks = tasks_per_day.get(&day_as_string).unwrap().clone();
-                current_tasks.push(worktime_in_seconds);
-                tasks_per_day.remove(&day_as_string);
-                tasks_per_day.insert(day_as_string.clone(), current_tasks);
-            } else {
-                tasks_per_day.insert(
-                    day_as_string.clone(),
-                    Vec::from([worktime_in_seconds]),
-                );
-                all_days.push(day_as_string);
-            }
+impl ExtraTimeReport {
+    fn new(
+        query_start: NaiveDate,
+        query_end: NaiveDate,
+        all_days: &[String],
+        work_duration_in_seconds_per_day: &HashMap<String, i64>,
+        extra_time_per_day: &HashMap<String, i64>,
+        cumulated_extra_time_per_day: &HashMap<String, i64>,
+    ) -> ExtraTimeReport {
+        let days: Vec<DailyEntry> = all_days
+            .iter()
+            .map(|day| {
+                let worked = *work_duration_in_seconds_per_day.get(day).unwrap();
+                let extra = *extra_time_per_day.get(day).unwrap();
+                DailyEntry {
+                    date: day.clone(),
+                    worked,
+                    expected: worked - extra,
+                    extra,
+                    cumulative: *cumulated_extra_time_per_day.get(day).unwrap(),
+                }
+            })
+            .collect();
+        let (weekday_days, weekend_days): (Vec<&DailyEntry>, Vec<&DailyEntry>) =
+            days.iter().partition(|day| {
+                is_working_day(NaiveDate::parse_from_str(&day.date, "%Y-%m-%d").unwrap())
+            });
+        ExtraTimeReport {
+            query_start: query_start.to_string(),
+            query_end: query_end.to_string(),
+            total: days.iter().map(|day| day.extra).sum(),
+            weekday_extra_seconds: weekday_days.iter().map(|day| day.extra).sum(),
+            weekend_extra_seconds: weekend_days.iter().map(|day| day.extra).sum(),
+            metadata: None,
+            days,
         }
+    }
 
-        all_days.sort();
+    /// The days of the report as a flat array, without the query dates nor the total.
+    fn to_flat_json(&self) -> Value {
+        Value::Array(
+            self.days
+                .iter()
+                .map(|day| {
+                    serde_json::json!({
+                        "date": day.date,
+                        "worked": day.worked,
+                        "extra": day.extra,
+                        "cumulative": day.cumulative,
+                    })
+                })
+                .collect(),
+        )
+    }
 
-        for day in &all_days {
-            let day = day.clone();
-            let tasks = tasks_per_day.get(&day).unwrap().clone();
-            let mut column_to_add_in_sheet = Vec::from([day.clone()]);
-            let mut total_worked_that_day = 0;
-            for task in tasks.iter() {
-                total_worked_that_day += task;
-                column_to_add_in_sheet.push(task.to_string());
+    /// The report keeping only `fields` for each day, as a flat array of days
+    /// when `flat` is set.
+    fn to_json_with_fields(&self, fields: &[&str], flat: bool) -> Value {
+        let days: Vec<Value> = self
+            .days
+            .iter()
+            .map(|day| {
+                Value::Object(
+                    fields
+                        .iter()
+                        .map(|field| {
+                            let value = match *field {
+                                "date" => Value::from(day.date.clone()),
+                                "worked" => Value::from(day.worked),
+                                "expected" => Value::from(day.expected),
+                                "extra" => Value::from(day.extra),
+                                "cumulative" => Value::from(day.cumulative),
+                                _ => unreachable!("unknown day field {}", field),
+                            };
+                            (field.to_string(), value)
+                        })
+                        .collect(),
+                )
+            })
+            .collect();
+        if flat {
+            return Value::Array(days);
+        }
+        let mut report = serde_json::to_value(self).unwrap();
+        report["days"] = Value::Array(days);
+        report
+    }
+}
+
+/// Renames the keys of every object in `value` following the `(old, new)`
+/// pairs of `renames`, returns the old names that were never found.
+fn rename_fields<'a>(value: &mut Value, renames: &[(&'a str, &str)]) -> Vec<&'a str> {
+    fn rename(value: &mut Value, renames: &[(&str, &str)], found: &mut HashSet<String>) {
+        match value {
+            Value::Object(object) => {
+                *object = std::mem::take(object)
+                    .into_iter()
+                    .map(|(key, mut child)| {
+                        rename(&mut child, renames, found);
+                        match renames.iter().find(|(old_name, _)| *old_name == key) {
+                            Some((_, new_name)) => {
+                                found.insert(key);
+                                (new_name.to_string(), child)
+                            }
+                            None => (key, child),
+                        }
+                    })
+                    .collect();
             }
-            sheet.add_column(column_to_add_in_sheet);
-            total_work_duration_per_day.insert(day.clone(), total_worked_that_day);
+            Value::Array(items) => {
+                for item in items {
+                    rename(item, renames, found);
+                }
+            }
+            _ => {}
         }
+    }
+    let mut found = HashSet::new();
+    rename(value, renames, &mut found);
+    renames
+        .iter()
+        .map(|(old_name, _)| *old_name)
+        .filter(|old_name| !found.contains(*old_name))
+        .collect()
+}
 
-        let mut total_extra_time_worked: i64 = 0;
+/// SHA-256 of the compact json of `report`, leaving out its metadata and
+/// checksum so that they do not change it.
+fn report_checksum(report: &Value) -> String {
+    let mut report = report.clone();
+    if let Some(object) = report.as_object_mut() {
+        object.shift_remove("metadata");
+        object.shift_remove("checksum");
+    }
+    format!(
+        "{:x}",
+        Sha256::digest(serde_json::to_string(&report).unwrap())
+    )
+}
+
+/// Validates the json file `file_name` against the JSON schema stored at
+/// `schema_path`, returns the violated constraints.
+fn validate_json_file(file_name: &str, schema_path: &Path) -> Vec<String> {
+    let schema_text = std::fs::read_to_string(schema_path).expect("Could not read schema file");
+    let schema: Value = serde_json::from_str(&schema_text).expect("Schema file is not valid JSON");
+    let output_text = std::fs::read_to_string(file_name).expect("Could not read JSON file");
+    let output: Value = serde_json::from_str(&output_text).unwrap();
+    match jsonschema::validator_for(&schema) {
+        Ok(validator) => validator
+            .iter_errors(&output)
+            .map(|error| format!("{}: {}", error.schema_path(), error.masked()))
+            .collect(),
+        Err(error) => vec![format!("Invalid schema: {}", error)],
+    }
+}
+
+/// Writes `value` as json, indented by `indent` spaces when set.
+fn write_json_file<T: Serialize>(file_name: &str, value: &T, indent: Option<usize>) {
+    let file = File::create(file_name).expect("Could not create JSON file");
+    match indent {
+        Some(indent) => {
+            let indent = " ".repeat(indent);
+            let formatter = serde_json::ser::PrettyFormatter::with_indent(indent.as_bytes());
+            let mut serializer = serde_json::Serializer::with_formatter(file, formatter);
+            value
+                .serialize(&mut serializer)
+                .expect("Could not write to JSON file");
+        }
+        None => serde_json::to_writer(file, value).expect("Could not write to JSON file"),
+    }
+}
+
+/// Writes the report as an Excel workbook, one row per day with the durations
+/// in hours, extra time cells coloured by sign. With `chart` set, a bar chart
+/// of the daily extra time is placed next to the table.
+fn write_excel_file(file_name: &str, report: &ExtraTimeReport, chart: bool) {
+    let mut workbook = Workbook::new();
+    let worksheet = workbook.add_worksheet();
+    let header_format = Format::new().set_bold();
+    let date_format = Format::new().set_num_format("yyyy-mm-dd");
+    let hours_format = Format::new().set_num_format("0.00");
+    let headers = [
+        "Date",
+        "Worked (h)",
+        "Expected (h)",
+        "Extra (h)",
+        "Cumulative (h)",
+    ];
+    for (col, header) in headers.iter().enumerate() {
+        worksheet
+            .write_string_with_format(0, col as u16, *header, &header_format)
+            .expect("Could not write to Excel file");
+    }
+    for (index, day) in report.days.iter().enumerate() {
+        let row = index as u32 + 1;
+        let date = ExcelDateTime::parse_from_str(&day.date).unwrap();
+        worksheet
+            .write_datetime_with_format(row, 0, date, &date_format)
+            .expect("Could not write to Excel file");
+        let durations = [day.worked, day.expected, day.extra, day.cumulative];
+        for (col, duration) in durations.iter().enumerate() {
+            worksheet
+                .write_number_with_format(
+                    row,
+                    col as u16 + 1,
+                    *duration as f64 / 3600.0,
+                    &hours_format,
+                )
+                .expect("Could not write to Excel file");
+        }
+    }
+    worksheet
+        .set_column_width(0, 12)
+        .expect("Could not write to Excel file");
+    // A header-only sheet has no range for the formats, the filter nor the chart
+    if !report.days.is_empty() {
+        let last_row = report.days.len() as u32;
+        let positive = ConditionalFormatCell::new()
+            .set_rule(ConditionalFormatCellRule::GreaterThan(0))
+            .set_format(Format::new().set_background_color("#C6EFCE"));
+        let negative = ConditionalFormatCell::new()
+            .set_rule(ConditionalFormatCellRule::LessThan(0))
+            .set_format(Format::new().set_background_color("#FFC7CE"));
+        worksheet
+            .add_conditional_format(1, 3, last_row, 4, &positive)
+            .expect("Could not write to Excel file");
+        worksheet
+            .add_conditional_format(1, 3, last_row, 4, &negative)
+            .expect("Could not write to Excel file");
+        worksheet
+            .autofilter(0, 0, last_row, headers.len() as u16 - 1)
+            .expect("Could not write to Excel file");
+        if chart {
+            let mut extra_chart = Chart::new(ChartType::Column);
+            extra_chart
+                .add_series()
+                .set_name("Extra (h)")
+                .set_categories(("Sheet1", 1, 0, last_row, 0))
+                .set_values(("Sheet1", 1, 3, last_row, 3))
+                .set_format(
+                    ChartFormat::new().set_solid_fill(ChartSolidFill::new().set_color("#00B050")),
+                )
+                .set_invert_if_negative_color("#FF0000");
+            extra_chart.title().set_name("Daily extra time");
+            extra_chart.x_axis().set_name("Date");
+            extra_chart.y_axis().set_name("Hours");
+            extra_chart.legend().set_hidden();
+            worksheet
+                .insert_chart(1, headers.len() as u16 + 1, &extra_chart)
+                .expect("Could not write to Excel file");
+        }
+    }
+    workbook
+        .save(file_name)
+        .expect("Could not create Excel file");
+}
+
+/// Errors raised while sending the report to an external service.
+#[derive(Debug)]
+enum CalculateError {
+    Io(std::io::Error),
+    Json(serde_json::Error),
+    Http(reqwest::Error),
+    Jwt(jsonwebtoken::errors::Error),
+    /// The service answered with an error status, kept with its body
+    Api(String),
+}
+
+impl std::fmt::Display for CalculateError {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        match self {
+            CalculateError::Io(error) => write!(f, "{}", error),
+            CalculateError::Json(error) => write!(f, "invalid JSON: {}", error),
+            CalculateError::Http(error) => write!(f, "request failed: {}", error),
+            CalculateError::Jwt(error) => write!(f, "could not sign token: {}", error),
+            CalculateError::Api(message) => write!(f, "{}", message),
+        }
+    }
+}
+
+impl From<std::io::Error> for CalculateError {
+    fn from(error: std::io::Error) -> CalculateError {
+        CalculateError::Io(error)
+    }
+}
+
+impl From<serde_json::Error> for CalculateError {
+    fn from(error: serde_json::Error) -> CalculateError {
+        CalculateError::Json(error)
+    }
+}
+
+impl From<reqwest::Error> for CalculateError {
+    fn from(error: reqwest::Error) -> CalculateError {
+        CalculateError::Http(error)
+    }
+}
+
+impl From<jsonwebtoken::errors::Error> for CalculateError {
+    fn from(error: jsonwebtoken::errors::Error) -> CalculateError {
+        CalculateError::Jwt(error)
+    }
+}
+
+/// Returns the response unchanged when its status is a success, its status
+/// and body as an error otherwise.
+fn check_response(
+    response: reqwest::blocking::Response,
+) -> Result<reqwest::blocking::Response, CalculateError> {
+    let status = response.status();
+    if status.is_success() {
+        Ok(response)
+    } else {
+        Err(CalculateError::Api(format!(
+            "{}: {}",
+            status,
+            response.text()?
+        )))
+    }
+}
+
+/// Fields of a Google service account key file used to authenticate.
+#[derive(Debug, Deserialize)]
+struct GoogleCredentials {
+    client_email: String,
+    private_key: String,
+    token_uri: String,
+}
+
+impl GoogleCredentials {
+    fn from_file(path: &Path) -> Result<GoogleCredentials, CalculateError> {
+        let text = std::fs::read_to_string(path)?;
+        Ok(serde_json::from_str(&text)?)
+    }
+}
+
+#[derive(Serialize)]
+struct GoogleClaims<'a> {
+    iss: &'a str,
+    scope: &'a str,
+    aud: &'a str,
+    iat: i64,
+    exp: i64,
+}
+
+/// Exchanges a token signed with the service account key for an access token
+/// allowed to edit spreadsheets.
+fn google_access_token(
+    client: &reqwest::blocking::Client,
+    creds: &GoogleCredentials,
+) -> Result<String, CalculateError> {
+    let now = chrono::Utc::now().timestamp();
+    let claims = GoogleClaims {
+        iss: &creds.client_email,
+        scope: "https://www.googleapis.com/auth/spreadsheets",
+        aud: &creds.token_uri,
+        iat: now,
+        exp: now + 3600,
+    };
+    let key = jsonwebtoken::EncodingKey::from_rsa_pem(creds.private_key.as_bytes())?;
+    let assertion = jsonwebtoken::encode(
+        &jsonwebtoken::Header::new(jsonwebtoken::Algorithm::RS256),
+        &claims,
+        &key,
+    )?;
+    let response = client
+        .post(&creds.token_uri)
+        .form(&[
+            ("grant_type", "urn:ietf:params:oauth:grant-type:jwt-bearer"),
+            ("assertion", assertion.as_str()),
+        ])
+        .send()?;
+    let body: Value = check_response(response)?.json()?;
+    match body["access_token"].as_str() {
+        Some(access_token) => Ok(access_token.to_string()),
+        None => Err(CalculateError::Api(String::from(
+            "no access token in the Google answer",
+        ))),
+    }
+}
+
+/// Writes the report to the sheet named after the query period of the
+/// spreadsheet `sheet_id`, creating the sheet when it does not exist yet.
+fn upload_to_sheets(
+    data: &ExtraTimeReport,
+    sheet_id: &str,
+    creds: &GoogleCredentials,
+) -> Result<(), CalculateError> {
+    let client = reqwest::blocking::Client::new();
+    let access_token = google_access_token(&client, creds)?;
+    let spreadsheet_url = format!("{}/spreadsheets/{}", GOOGLE_SHEETS_API_URL, sheet_id);
+    let sheet_title = format!("{} - {}", data.query_start, data.query_end);
+
+    let response = client
+        .get(&spreadsheet_url)
+        .bearer_auth(&access_token)
+        .query(&[("fields", "sheets.properties.title")])
+        .send()?;
+    let spreadsheet: Value = check_response(response)?.json()?;
+    let sheet_exists = spreadsheet["sheets"]
+        .as_array()
+        .into_iter()
+        .flatten()
+        .any(|sheet| sheet["properties"]["title"] == sheet_title.as_str());
+    if !sheet_exists {
+        let response = client
+            .post(format!("{}:batchUpdate", spreadsheet_url))
+            .bearer_auth(&access_token)
+            .json(&serde_json::json!({
+                "requests": [{"addSheet": {"properties": {"title": sheet_title}}}]
+            }))
+            .send()?;
+        check_response(response)?;
+    }
+
+    let hours = |seconds: i64| seconds as f64 / 3600.0;
+    let mut rows = Vec::from([serde_json::json!([
+        "Date",
+        "Worked (h)",
+        "Expected (h)",
+        "Extra (h)",
+        "Cumulative (h)"
+    ])]);
+    for day in &data.days {
+        rows.push(serde_json::json!([
+            day.date,
+            hours(day.worked),
+            hours(day.expected),
+            hours(day.extra),
+            hours(day.cumulative)
+        ]));
+    }
+    rows.push(serde_json::json!(["Total", "", "", hours(data.total), ""]));
+
+    let mut values_url = reqwest::Url::parse(&spreadsheet_url).unwrap();
+    values_url
+        .path_segments_mut()
+        .unwrap()
+        .push("values")
+        .push(&format!("'{}'!A1", sheet_title));
+    let response = client
+        .put(values_url)
+        .bearer_auth(&access_token)
+        .query(&[("valueInputOption", "USER_ENTERED")])
+        .json(&serde_json::json!({ "values": rows }))
+        .send()?;
+    check_response(response)?;
+    Ok(())
+}
+
+/// Notion block holding a single line of plain text of the given type.
+fn notion_text_block(block_type: &str, text: &str) -> Value {
+    serde_json::json!({
+        "object": "block",
+        "type": block_type,
+        block_type: {"rich_text": [{"type": "text", "text": {"content": text}}]}
+    })
+}
+
+/// Notion table row made of plain text cells.
+fn notion_table_row(cells: &[String]) -> Value {
+    let cells: Vec<Value> = cells
+        .iter()
+        .map(|cell| serde_json::json!([{"type": "text", "text": {"content": cell}}]))
+        .collect();
+    serde_json::json!({"object": "block", "type": "table_row", "table_row": {"cells": cells}})
+}
+
+/// Creates a page under `parent_id` showing the report as a heading, a callout
+/// with the total and a table of the days, returns the URL of the new page.
+fn create_notion_page(
+    report: &ExtraTimeReport,
+    parent_id: &str,
+    token: &str,
+) -> Result<String, CalculateError> {
+    let title = format!(
+        "Extra time from {} to {}",
+        report.query_start, report.query_end
+    );
+    let mut children = Vec::from([
+        notion_text_block("heading_2", &title),
+        serde_json::json!({
+            "object": "block",
+            "type": "callout",
+            "callout": {
+                "rich_text": [{"type": "text", "text": {"content": format!(
+                    "Total extra time : {}",
+                    format_signed_hours_minutes(report.total)
+                )}}],
+                "icon": {"type": "emoji", "emoji": if report.total >= 0 { "✅" } else { "⚠️" }}
+            }
+        }),
+    ]);
+    // A block accepts at most 100 children, the days are split across tables
+    for days in report.days.chunks(NOTION_MAX_BLOCK_CHILDREN - 1) {
+        let mut rows = Vec::from([notion_table_row(&[
+            String::from("Date"),
+            String::from("Worked"),
+            String::from("Expected"),
+            String::from("Extra"),
+            String::from("Cumulative"),
+        ])]);
+        for day in days {
+            rows.push(notion_table_row(&[
+                day.date.clone(),
+                format_hours_minutes(day.worked),
+                format_hours_minutes(day.expected),
+                format_signed_hours_minutes(day.extra),
+                format_signed_hours_minutes(day.cumulative),
+            ]));
+        }
+        children.push(serde_json::json!({
+            "object": "block",
+            "type": "table",
+            "table": {
+                "table_width": 5,
+                "has_column_header": true,
+                "has_row_header": false,
+                "children": rows
+            }
+        }));
+    }
+    let response = reqwest::blocking::Client::new()
+        .post(format!("{}/pages", NOTION_API_URL))
+        .bearer_auth(token)
+        .header("Notion-Version", NOTION_API_VERSION)
+        .json(&serde_json::json!({
+            "parent": {"page_id": parent_id},
+            "properties": {"title": {"title": [{"type": "text", "text": {"content": title}}]}},
+            "children": children
+        }))
+        .send()?;
+    let page: Value = check_response(response)?.json()?;
+    match page["url"].as_str() {
+        Some(url) => Ok(url.to_string()),
+        None => Err(CalculateError::Api(String::from(
+            "no page URL in the Notion answer",
+        ))),
+    }
+}
+
+/// Upserts one record per day of the report into `table` of the Airtable base
+/// `base_id`, records being matched on their date.
+fn sync_to_airtable(
+    report: &ExtraTimeReport,
+    base_id: &str,
+    table: &str,
+    token: &str,
+) -> Result<(), CalculateError> {
+    let client = reqwest::blocking::Client::new();
+    let mut table_url = reqwest::Url::parse(AIRTABLE_API_URL).unwrap();
+    table_url
+        .path_segments_mut()
+        .unwrap()
+        .push(base_id)
+        .push(table);
+    let hours = |seconds: i64| seconds as f64 / 3600.0;
+    for days in report.days.chunks(AIRTABLE_MAX_RECORDS_PER_REQUEST) {
+        let records: Vec<Value> = days
+            .iter()
+            .map(|day| {
+                serde_json::json!({"fields": {
+                    "Date": day.date,
+                    "Worked hours": hours(day.worked),
+                    "Expected hours": hours(day.expected),
+                    "Extra time": hours(day.extra)
+                }})
+            })
+            .collect();
+        let response = client
+            .patch(table_url.clone())
+            .bearer_auth(token)
+            .json(&serde_json::json!({
+                "performUpsert": {"fieldsToMergeOn": ["Date"]},
+                "typecast": true,
+                "records": records
+            }))
+            .send()?;
+        check_response(response)?;
+    }
+    Ok(())
+}
+
+#[derive(Debug, Clone)]
+struct Entry {
+    id: i64,
+    start: DateTime<FixedOffset>,
+    duration: i64,
+    description: String,
+    project_id: Option<i64>,
+    tags: Vec<String>,
+    billable: bool,
+}
+
+impl Entry {
+    fn end(&self) -> DateTime<FixedOffset> {
+        self.start + TimeDelta::seconds(self.duration)
+    }
+}
+
+/// Seconds of the entry falling between `start` and `end` on each of the days
+/// it spans.
+fn clip_entry_to_window(entry: &Entry, start: NaiveTime, end: NaiveTime) -> i64 {
+    let offset = *entry.start.offset();
+    let entry_end = entry.end();
+    let mut clipped = 0;
+    let mut date = entry.start.date_naive();
+    while date <= entry_end.date_naive() {
+        let window_start = date.and_time(start).and_local_timezone(offset).unwrap();
+        let window_end = date.and_time(end).and_local_timezone(offset).unwrap();
+        clipped += (entry_end.min(window_end) - entry.start.max(window_start))
+            .num_seconds()
+            .max(0);
+        date = date.succ_opt().unwrap();
+    }
+    clipped
+}
+
+/// Replaces the `{field}` placeholders of `format` with the fields of the
+/// entry, unknown placeholders are left as they are. The template is read
+/// once, so placeholders inside the entry fields are not expanded.
+fn format_entry(entry: &Entry, format: &str, project_names: &HashMap<i64, String>) -> String {
+    let project = entry
+        .project_id
+        .and_then(|id| project_names.get(&id))
+        .cloned()
+        .unwrap_or_default();
+    let fields = [
+        ("id", entry.id.to_string()),
+        ("date", entry.start.date_naive().to_string()),
+        ("start", entry.start.format("%H:%M").to_string()),
+        ("end", entry.end().format("%H:%M").to_string()),
+        ("duration", format_hms(entry.duration, false)),
+        ("duration_seconds", entry.duration.to_string()),
+        ("project", project),
+        ("description", entry.description.clone()),
+        ("tags", entry.tags.join(", ")),
+        ("billable", entry.billable.to_string()),
+    ];
+    let mut formatted = String::new();
+    let mut rest = format;
+    while let Some(open) = rest.find('{') {
+        formatted.push_str(&rest[..open]);
+        let after = &rest[open + 1..];
+        let field = after.find('}').and_then(|close| {
+            fields
+                .iter()
+                .find(|(name, _)| *name == &after[..close])
+                .map(|(_, value)| (close, value))
+        });
+        match field {
+            Some((close, value)) => {
+                formatted.push_str(value);
+                rest = &after[close + 1..];
+            }
+            None => {
+                formatted.push('{');
+                rest = after;
+            }
+        }
+    }
+    formatted.push_str(rest);
+    formatted
+}
+
+/// Writes one line per entry following the `format` template.
+fn write_formatted_entries(
+    entries: &[Entry],
+    format: &str,
+    project_names: &HashMap<i64, String>,
+    path: &Path,
+) -> Result<(), CalculateError> {
+    let mut file = File::create(path)?;
+    for entry in entries {
+        writeln!(&mut file, "{}", format_entry(entry, format, project_names))?;
+    }
+    Ok(())
+}
+
+/// Writes one RFC 4180 row per entry, with the names of its project and of
+/// the client of that project.
+fn write_entries_csv(
+    entries: &[Entry],
+    project_names: &HashMap<i64, String>,
+    client_names: &HashMap<i64, String>,
+    path: &Path,
+) -> Result<(), CalculateError> {
+    let quote = |cell: &str| {
+        if cell.contains([',', '"', '\n', '\r']) {
+            format!("\"{}\"", cell.replace('"', "\"\""))
+        } else {
+            cell.to_string()
+        }
+    };
+    let mut file = File::create(path)?;
+    write!(
+        &mut file,
+        "date,entry_id,start_time,end_time,duration_seconds,project_id,project_name,\
+         client_name,description,tags,billable\r\n"
+    )?;
+    for entry in entries {
+        let project_name = entry.project_id.and_then(|id| project_names.get(&id));
+        let client_name = entry.project_id.and_then(|id| client_names.get(&id));
+        let cells = [
+            entry.start.date_naive().to_string(),
+            entry.id.to_string(),
+            entry.start.to_rfc3339(),
+            entry.end().to_rfc3339(),
+            entry.duration.to_string(),
+            entry
+                .project_id
+                .map(|id| id.to_string())
+                .unwrap_or_default(),
+            project_name.cloned().unwrap_or_default(),
+            client_name.cloned().unwrap_or_default(),
+            entry.description.clone(),
+            entry.tags.join(","),
+            entry.billable.to_string(),
+        ];
+        let row: Vec<String> = cells.iter().map(|cell| quote(cell)).collect();
+        write!(&mut file, "{}\r\n", row.join(","))?;
+    }
+    Ok(())
+}
+
+/// Entries following each other with short gaps in between.
+#[derive(Debug)]
+struct WorkSession<'a> {
+    start: DateTime<FixedOffset>,
+    end: DateTime<FixedOffset>,
+    total_duration_secs: i64,
+    entries: Vec<&'a Entry>,
+}
+
+/// Groups entries into sessions, a new session starting whenever an entry
+/// begins more than `max_gap_secs` after the end of the previous ones.
+fn detect_sessions(entries: &[Entry], max_gap_secs: i64) -> Vec<WorkSession<'_>> {
+    let mut sorted_entries: Vec<&Entry> = entries.iter().collect();
+    sorted_entries.sort_by_key(|entry| entry.start);
+    let mut sessions: Vec<WorkSession> = Vec::new();
+    for entry in sorted_entries {
+        match sessions.last_mut() {
+            Some(session) if (entry.start - session.end).num_seconds() <= max_gap_secs => {
+                session.end = session.end.max(entry.end());
+                session.total_duration_secs += entry.duration;
+                session.entries.push(entry);
+            }
+            _ => sessions.push(WorkSession {
+                start: entry.start,
+                end: entry.end(),
+                total_duration_secs: entry.duration,
+                entries: vec![entry],
+            }),
+        }
+    }
+    sessions
+}
+
+/// Total time between consecutive sessions, which must be sorted by start.
+fn total_break_time(sessions: &[WorkSession]) -> i64 {
+    sessions
+        .windows(2)
+        .map(|pair| (pair[1].start - pair[0].end).num_seconds().max(0))
+        .sum()
+}
+
+/// Time between the start of the first entry and the end of the last one that
+/// is neither logged nor part of a break between sessions, i.e. the short
+/// gaps left uncovered inside the sessions.
+fn uncovered_time(entries: &[Entry], max_gap_secs: i64) -> i64 {
+    let sessions = detect_sessions(entries, max_gap_secs);
+    match (sessions.first(), sessions.last()) {
+        (Some(first), Some(last)) => {
+            let span = (last.end - first.start).num_seconds();
+            let logged: i64 = entries.iter().map(|entry| entry.duration).sum();
+            (span - logged - total_break_time(&sessions)).max(0)
+        }
+        _ => 0,
+    }
+}
+
+/// Finds the longest gap between two entries that overlaps the 11:00–14:00
+/// window, returns its start, end and duration in seconds.
+fn detect_lunch_break(entries: &[Entry]) -> Option<(NaiveTime, NaiveTime, i64)> {
+    let window_start = NaiveTime::from_hms_opt(11, 0, 0).unwrap();
+    let window_end = NaiveTime::from_hms_opt(14, 0, 0).unwrap();
+    let mut sorted_entries: Vec<&Entry> = entries.iter().collect();
+    sorted_entries.sort_by_key(|entry| entry.start);
+    let mut lunch_break: Option<(NaiveTime, NaiveTime, i64)> = None;
+    let mut previous_end: Option<DateTime<FixedOffset>> = None;
+    for entry in sorted_entries {
+        if let Some(gap_start) = previous_end
+            && entry.start > gap_start
+            && gap_start.date_naive() == entry.start.date_naive()
+        {
+            let (start, end) = (gap_start.time(), entry.start.time());
+            let duration = (entry.start - gap_start).num_seconds();
+            let overlaps_window = start < window_end && end > window_start;
+            if overlaps_window && lunch_break.is_none_or(|(_, _, longest)| duration > longest) {
+                lunch_break = Some((start, end, duration));
+            }
+        }
+        previous_end = Some(match previous_end {
+            Some(previous_end) => previous_end.max(entry.end()),
+            None => entry.end(),
+        });
+    }
+    lunch_break
+}
+
+#[derive(Debug)]
+struct TimeOfDayStats {
+    average: NaiveTime,
+    earliest: (String, NaiveTime),
+    latest: (String, NaiveTime),
+}
+
+/// Average, earliest and latest of the given times. `times` must not be empty.
+fn time_of_day_stats(mut times: Vec<(String, NaiveTime)>) -> TimeOfDayStats {
+    times.sort();
+    let average_seconds = times
+        .iter()
+        .map(|(_, time)| time.num_seconds_from_midnight() as u64)
+        .sum::<u64>()
+        / times.len() as u64;
+    TimeOfDayStats {
+        average: NaiveTime::from_num_seconds_from_midnight_opt(average_seconds as u32, 0).unwrap(),
+        earliest: times.iter().min_by_key(|(_, time)| *time).unwrap().clone(),
+        latest: times.iter().max_by_key(|(_, time)| *time).unwrap().clone(),
+    }
+}
+
+/// Statistics on the start time of the first entry of each day. `days` must
+/// not be empty.
+fn start_time_stats(days: &HashMap<String, Vec<Entry>>) -> TimeOfDayStats {
+    time_of_day_stats(
+        days.iter()
+            .map(|(day, entries)| {
+                let first_start = entries.iter().map(|entry| entry.start).min().unwrap();
+                (day.clone(), first_start.time())
+            })
+            .collect(),
+    )
+}
+
+/// Statistics on the end time of the last entry of each day. `days` must not
+/// be empty.
+fn end_time_stats(days: &HashMap<String, Vec<Entry>>) -> TimeOfDayStats {
+    time_of_day_stats(
+        days.iter()
+            .map(|(day, entries)| {
+                let last_end = entries.iter().map(|entry| entry.end()).max().unwrap();
+                (day.clone(), last_end.time())
+            })
+            .collect(),
+    )
+}
+
+#[derive(Debug, Clone)]
+struct WeeklySummary {
+    start: NaiveDate,
+    end: NaiveDate,
+    worked_seconds: i64,
+    extra_seconds: i64,
+}
+
+fn format_duration(seconds: i64) -> String {
+    let duration: TimeDelta = TimeDelta::seconds(seconds.abs());
+    let hours = duration.num_hours();
+    let minutes = duration.num_minutes() - (hours * 60);
+    let seconds_left = duration.num_seconds() - (hours * 60 * 60) - (minutes * 60);
+    let sign = if seconds < 0 { "-" } else { "" };
+    format!("{}{}h{}min{}sec", sign, hours, minutes, seconds_left)
+}
+
+fn weekday_name(weekday: Weekday) -> &'static str {
+    match weekday {
+        Weekday::Mon => "Monday",
+        Weekday::Tue => "Tuesday",
+        Weekday::Wed => "Wednesday",
+        Weekday::Thu => "Thursday",
+        Weekday::Fri => "Friday",
+        Weekday::Sat => "Saturday",
+        Weekday::Sun => "Sunday",
+    }
+}
+
+fn is_working_day(date: NaiveDate) -> bool {
+    !matches!(date.weekday(), Weekday::Sat | Weekday::Sun)
+}
+
+/// `date` when it is a working day, the next working day otherwise.
+fn first_working_day_from(date: NaiveDate) -> NaiveDate {
+    if is_working_day(date) {
+        date
+    } else {
+        nth_working_day_after(date, 1)
+    }
+}
+
+/// The `n`th working day after `date`.
+fn nth_working_day_after(date: NaiveDate, n: u32) -> NaiveDate {
+    date.iter_days()
+        .skip(1)
+        .filter(|day| is_working_day(*day))
+        .nth(n.saturating_sub(1) as usize)
+        .unwrap()
+}
+
+/// Working days between the first and the last of the sorted `all_days` that
+/// have no entry.
+fn missing_working_days(all_days: &[String]) -> Vec<String> {
+    let (Some(first_day), Some(last_day)) = (all_days.first(), all_days.last()) else {
+        return Vec::new();
+    };
+    let first_day = NaiveDate::parse_from_str(first_day, "%Y-%m-%d").unwrap();
+    let last_day = NaiveDate::parse_from_str(last_day, "%Y-%m-%d").unwrap();
+    first_day
+        .iter_days()
+        .take_while(|date| *date <= last_day)
+        .filter(|date| is_working_day(*date))
+        .map(|date| date.format("%Y-%m-%d").to_string())
+        .filter(|day| !all_days.contains(day))
+        .collect()
+}
+
+/// Formats a duration in natural language, e.g. "1 day, 2 hours, 15 minutes".
+/// A `balance` of zero reads "no extra time" and a negative one is reported
+/// as time short, e.g. "30 minutes short".
+fn humanise_duration(seconds: i64, balance: bool) -> String {
+    if balance && seconds == 0 {
+        return String::from("no extra time");
+    }
+    let abs = seconds.abs();
+    let units = [
+        (abs / 86400, "day"),
+        ((abs % 86400) / 3600, "hour"),
+        ((abs % 3600) / 60, "minute"),
+    ];
+    let mut parts: Vec<String> = units
+        .iter()
+        .filter(|(value, _)| *value > 0)
+        .map(|(value, unit)| format!("{} {}{}", value, unit, if *value > 1 { "s" } else { "" }))
+        .collect();
+    if parts.is_empty() {
+        parts.push(format!("{} second{}", abs, if abs != 1 { "s" } else { "" }));
+    }
+    let text = match parts.len() {
+        1 => parts[0].clone(),
+        2 => format!("{} and {}", parts[0], parts[1]),
+        _ => parts.join(", "),
+    };
+    match (seconds < 0, balance) {
+        (true, true) => format!("{} short", text),
+        (true, false) => format!("-{}", text),
+        (false, _) => text,
+    }
+}
+
+/// Formats a duration as HH:MM:SS, with an explicit sign when `signed` is set.
+fn format_hms(seconds: i64, signed: bool) -> String {
+    let abs = seconds.abs();
+    let sign = if seconds < 0 {
+        "-"
+    } else if signed {
+        "+"
+    } else {
+        ""
+    };
+    format!(
+        "{}{:02}:{:02}:{:02}",
+        sign,
+        abs / 3600,
+        (abs % 3600) / 60,
+        abs % 60
+    )
+}
+
+fn format_hours_minutes(seconds: i64) -> String {
+    let sign = if seconds < 0 { "-" } else { "" };
+    let abs = seconds.abs();
+    format!("{}{}h {}min", sign, abs / 3600, (abs % 3600) / 60)
+}
+
+fn format_signed_hours_minutes(seconds: i64) -> String {
+    let sign = if seconds > 0 { "+" } else { "" };
+    format!("{}{}", sign, format_hours_minutes(seconds))
+}
+
+/// Number of months between `start` and `end` (both included), partial months
+/// counting for the fraction of their days that is covered.
+fn months_in_range(start: NaiveDate, end: NaiveDate) -> f64 {
+    let mut months = 0.0;
+    let mut month_start = start.with_day(1).unwrap();
+    while month_start <= end {
+        let next_month_start = month_start + Months::new(1);
+        let days_in_month = (next_month_start - month_start).num_days();
+        let first_day = start.max(month_start);
+        let last_day = end.min(next_month_start.pred_opt().unwrap());
+        months += ((last_day - first_day).num_days() + 1) as f64 / days_in_month as f64;
+        month_start = next_month_start;
+    }
+    months
+}
+
+/// Number of weeks between `start` and `end` (both included), a partial week
+/// counting for the fraction of its days that is covered.
+fn weeks_in_range(start: NaiveDate, end: NaiveDate) -> f64 {
+    ((end - start).num_days() + 1).max(0) as f64 / 7.0
+}
+
+fn compute_weekly_summaries(
+    all_days: &[String],
+    work_duration_in_seconds_per_day: &HashMap<String, i64>,
+    extra_time_per_day: &HashMap<String, i64>,
+) -> Vec<WeeklySummary> {
+    let mut weeks: Vec<WeeklySummary> = Vec::new();
+    for day in all_days {
+        let date = NaiveDate::parse_from_str(day, "%Y-%m-%d").unwrap();
+        let week = date.week(Weekday::Mon);
+        let worked = *work_duration_in_seconds_per_day.get(day).unwrap();
+        let extra = *extra_time_per_day.get(day).unwrap();
+        match weeks.last_mut() {
+            Some(summary) if summary.start == week.first_day() => {
+                summary.worked_seconds += worked;
+                summary.extra_seconds += extra;
+            }
+            _ => weeks.push(WeeklySummary {
+                start: week.first_day(),
+                end: week.last_day(),
+                worked_seconds: worked,
+                extra_seconds: extra,
+            }),
+        }
+    }
+    weeks
+}
+
+/// Returns the (worst, best) weeks, by extra time. `weeks` must not be empty.
+fn find_extreme_weeks(weeks: &[WeeklySummary]) -> (WeeklySummary, WeeklySummary) {
+    let worst = weeks.iter().min_by_key(|week| week.extra_seconds).unwrap();
+    let best = weeks.iter().max_by_key(|week| week.extra_seconds).unwrap();
+    (worst.clone(), best.clone())
+}
+
+/// Repeats the characters of `separator` to draw a rule `width` characters long.
+fn separator_line(separator: &str, width: usize) -> String {
+    separator.chars().cycle().take(width).collect()
+}
+
+/// Hides all but the last four characters of an API token.
+fn mask_token(token: &str) -> String {
+    let visible = token.chars().count().saturating_sub(4);
+    token
+        .chars()
+        .enumerate()
+        .map(|(i, c)| if i < visible { '*' } else { c })
+        .collect()
+}
+
+/// Sends the requests to the Toggl API on behalf of the user.
+struct TogglClient {
+    client: reqwest::blocking::Client,
+    token: String,
+    show_quota: bool,
+    show_response_size: bool,
+    compress: bool,
+    print_curl: bool,
+    call_count: std::cell::Cell<usize>,
+    /// JSON file answered instead of the time entries of the API, no request
+    /// is sent when set
+    fixture: Option<PathBuf>,
+    /// Diagnostics go to stderr, stdout carrying the --ndjson stream
+    ndjson: bool,
+}
+
+impl TogglClient {
+    fn new(token: &str, opt: &Opt) -> TogglClient {
+        TogglClient {
+            // reqwest hides the compressed body, so it is decompressed by hand when its size is shown
+            client: reqwest::blocking::Client::builder()
+                .gzip(opt.compress_response && !opt.show_api_response_size)
+                .build()
+                .unwrap(),
+            token: token.to_string(),
+            show_quota: opt.show_api_quota,
+            show_response_size: opt.show_api_response_size,
+            compress: opt.compress_response,
+            print_curl: opt.curl_equivalent,
+            call_count: std::cell::Cell::new(0),
+            fixture: opt.mock_api.clone(),
+            ndjson: opt.ndjson,
+        }
+    }
+
+    fn print_diagnostic(&self, line: &str) {
+        if self.ndjson {
+            eprintln!("{}", line);
+        } else {
+            println!("{}", line);
+        }
+    }
+
+    /// Number of requests sent so far.
+    fn call_count(&self) -> usize {
+        self.call_count.get()
+    }
+
+    /// Sends a GET request to `url`, returns the body of the answer.
+    fn get(&self, url: &str) -> String {
+        self.call_count.set(self.call_count.get() + 1);
+        if self.print_curl {
+            eprintln!(
+                "curl -s{} -u \"$TOGGL_API_TOKEN:api_token\" \"{}\"",
+                if self.compress { " --compressed" } else { "" },
+                url
+            );
+        }
+        let decompress_by_hand = self.compress && self.show_response_size;
+        let mut request = self
+            .client
+            .get(url)
+            .basic_auth(&self.token, Some("api_token"));
+        if decompress_by_hand {
+            request = request.header(reqwest::header::ACCEPT_ENCODING, "gzip");
+        }
+        let response = request.send().unwrap();
+        if self.show_quota {
+            self.print_diagnostic(&api_quota(response.headers()));
+        }
+        let gzipped = decompress_by_hand
+            && response
+                .headers()
+                .get(reqwest::header::CONTENT_ENCODING)
+                .is_some_and(|encoding| encoding == "gzip");
+        if !gzipped {
+            return response.text().unwrap();
+        }
+        let body = response.bytes().unwrap();
+        let mut text = String::new();
+        flate2::read::GzDecoder::new(&body[..])
+            .read_to_string(&mut text)
+            .unwrap();
+        self.print_diagnostic(&format!(
+            "API response compressed size: {} bytes, decompressed size: {} bytes",
+            body.len(),
+            text.len()
+        ));
+        text
+    }
+
+    fn fetch_time_entries(&self, url: &str) -> Vec<Value> {
+        let resp_text = match &self.fixture {
+            Some(fixture) => {
+                self.call_count.set(self.call_count.get() + 1);
+                std::fs::read_to_string(fixture).expect("Could not read the mock API file")
+            }
+            None => self.get(url),
+        };
+        let entries: Vec<Value> = serde_json::from_str(&resp_text).unwrap();
+        if self.show_response_size {
+            self.print_diagnostic(&format!(
+                "API response size: {} bytes ({} entries)",
+                resp_text.len(),
+                entries.len()
+            ));
+        }
+        entries
+    }
+
+    /// Fetches `/me/<path>` of the API, e.g. the projects or the clients. The
+    /// fixture only holds time entries, so nothing is found when one is set.
+    fn fetch_me(&self, path: &str) -> Value {
+        if self.fixture.is_some() {
+            self.call_count.set(self.call_count.get() + 1);
+            return Value::Array(Vec::new());
+        }
+        let resp_text = self.get(&format!("{}/me/{}", TOGGL_API_URL, path));
+        let value: Value = serde_json::from_str(&resp_text).unwrap();
+        if self.show_response_size {
+            self.print_diagnostic(&format!(
+                "API response size: {} bytes ({} entries)",
+                resp_text.len(),
+                value.as_array().map_or(0, |items| items.len())
+            ));
+        }
+        value
+    }
+}
+
+/// Describes the quota left as told by the rate limit headers of an answer.
+fn api_quota(headers: &reqwest::header::HeaderMap) -> String {
+    let header = |name: &str| {
+        headers
+            .get(name)
+            .and_then(|value| value.to_str().ok())
+            .and_then(|value| value.parse::<i64>().ok())
+    };
+    match (header("X-RateLimit-Remaining"), header("X-RateLimit-Reset")) {
+        (Some(remaining), Some(reset)) => {
+            // The reset is either a timestamp or a number of seconds from now
+            let reset_at = if reset > 1_000_000_000 {
+                DateTime::from_timestamp(reset, 0).unwrap()
+            } else {
+                chrono::Utc::now() + TimeDelta::seconds(reset)
+            };
+            format!(
+                "API quota remaining: {} requests, resets at {}",
+                remaining,
+                reset_at
+                    .with_timezone(&chrono::Local)
+                    .format("%Y-%m-%d %H:%M:%S")
+            )
+        }
+        (Some(remaining), None) => format!("API quota remaining: {} requests", remaining),
+        _ => String::from("API quota remaining: unknown, no rate limit headers in the answer"),
+    }
+}
+
+/// Names of the projects fetched from `/me/projects`, indexed by id.
+fn project_names(projects: &Value) -> HashMap<i64, String> {
+    let mut project_names = HashMap::new();
+    for project in projects.as_array().into_iter().flatten() {
+        project_names.insert(
+            project["id"].as_i64().unwrap(),
+            project["name"].as_str().unwrap().to_string(),
+        );
+    }
+    project_names
+}
+
+/// Fetches the name of the client of each of the `projects`, indexed by
+/// project id. Projects without a client are left out.
+fn fetch_client_names(toggl: &TogglClient, projects: &Value) -> HashMap<i64, String> {
+    let mut names_per_client = HashMap::new();
+    for toggl_client in toggl.fetch_me("clients").as_array().into_iter().flatten() {
+        names_per_client.insert(
+            toggl_client["id"].as_i64().unwrap(),
+            toggl_client["name"].as_str().unwrap().to_string(),
+        );
+    }
+    let mut client_names = HashMap::new();
+    for project in projects.as_array().into_iter().flatten() {
+        if let Some(name) = project["client_id"]
+            .as_i64()
+            .and_then(|client_id| names_per_client.get(&client_id))
+        {
+            client_names.insert(project["id"].as_i64().unwrap(), name.clone());
+        }
+    }
+    client_names
+}
+
+fn terminal_width() -> usize {
+    match terminal_size::terminal_size() {
+        Some((terminal_size::Width(width), _)) => width as usize,
+        None => 80,
+    }
+}
+
+/// Lines of a table whose columns are padded to the same width. When the table
+/// is wider than `max_width`, the columns are split across several blocks, each
+/// one starting with the first column again.
+fn format_table(
+    headers: &[String],
+    rows: &[Vec<String>],
+    max_width: usize,
+    row_separator: Option<&str>,
+) -> Vec<String> {
+    let mut lines = Vec::new();
+    let widths: Vec<usize> = (0..headers.len())
+        .map(|i| {
+            rows.iter()
+                .map(|row| row[i].chars().count())
+                .chain([headers[i].chars().count()])
+                .max()
+                .unwrap()
+        })
+        .collect();
+
+    let mut blocks: Vec<Vec<usize>> = Vec::new();
+    let mut current_block = vec![0];
+    let mut current_width = widths[0];
+    for (i, width) in widths.iter().enumerate().skip(1) {
+        if current_width + 2 + width > max_width && current_block.len() > 1 {
+            blocks.push(current_block);
+            current_block = vec![0];
+            current_width = widths[0];
+        }
+        current_block.push(i);
+        current_width += 2 + width;
+    }
+    blocks.push(current_block);
+
+    for (block_index, block) in blocks.iter().enumerate() {
+        if block_index > 0 {
+            lines.push(String::new());
+        }
+        let format_row = |cells: &[String]| {
+            block
+                .iter()
+                .map(|&i| format!("{:>width$}", cells[i], width = widths[i]))
+                .collect::<Vec<String>>()
+                .join("  ")
+        };
+        let header_line = format_row(headers);
+        let line_width = header_line.chars().count();
+        lines.push(header_line);
+        for row in rows {
+            if let Some(separator) = row_separator {
+                lines.push(separator_line(separator, line_width));
+            }
+            lines.push(format_row(row));
+        }
+    }
+    lines
+}
+
+/// Average time worked for each day of the week, indexed by `Weekday as usize`.
+/// Days of the week absent from `days` are left at 0.
+fn group_by_weekday(days: &[NaiveDate], durations: &HashMap<String, i64>) -> [i64; 7] {
+    let mut totals = [0; 7];
+    let mut counts = [0; 7];
+    for day in days {
+        let index = day.weekday() as usize;
+        totals[index] += durations.get(&day.format("%Y-%m-%d").to_string()).unwrap();
+        counts[index] += 1;
+    }
+    let mut averages = [0; 7];
+    for index in 0..7 {
+        if counts[index] > 0 {
+            averages[index] = totals[index] / counts[index];
+        }
+    }
+    averages
+}
+
+/// Least squares fit of `y = slope * x + intercept`, returns `(slope, intercept)`.
+fn linear_regression(x: &[f64], y: &[f64]) -> (f64, f64) {
+    let n = x.len() as f64;
+    let mean_x = x.iter().sum::<f64>() / n;
+    let mean_y = y.iter().sum::<f64>() / n;
+    let covariance: f64 = x
+        .iter()
+        .zip(y)
+        .map(|(xi, yi)| (xi - mean_x) * (yi - mean_y))
+        .sum();
+    let variance: f64 = x.iter().map(|xi| (xi - mean_x).powi(2)).sum();
+    let slope = if variance == 0.0 {
+        0.0
+    } else {
+        covariance / variance
+    };
+    (slope, mean_y - slope * mean_x)
+}
+
+/// Pearson correlation coefficient of `x` and `y`, 0 when either is constant.
+fn pearson_correlation(x: &[f64], y: &[f64]) -> f64 {
+    let n = x.len() as f64;
+    let mean_x = x.iter().sum::<f64>() / n;
+    let mean_y = y.iter().sum::<f64>() / n;
+    let covariance: f64 = x
+        .iter()
+        .zip(y)
+        .map(|(xi, yi)| (xi - mean_x) * (yi - mean_y))
+        .sum();
+    let variance_x: f64 = x.iter().map(|xi| (xi - mean_x).powi(2)).sum();
+    let variance_y: f64 = y.iter().map(|yi| (yi - mean_y).powi(2)).sum();
+    if variance_x == 0.0 || variance_y == 0.0 {
+        0.0
+    } else {
+        covariance / (variance_x * variance_y).sqrt()
+    }
+}
+
+fn describe_correlation(r: f64) -> &'static str {
+    match (r.abs(), r > 0.0) {
+        (strength, _) if strength < 0.1 => "none",
+        (strength, true) if strength < 0.3 => "weak positive",
+        (strength, false) if strength < 0.3 => "weak negative",
+        (strength, true) if strength < 0.5 => "moderate positive",
+        (strength, false) if strength < 0.5 => "moderate negative",
+        (_, true) => "strong positive",
+        (_, false) => "strong negative",
+    }
+}
+
+/// 95% confidence interval of the mean of `values`, using the t-distribution
+/// for samples of up to 101 values and the normal distribution above.
+/// `values` must hold at least two values.
+fn confidence_interval_95(values: &[i64]) -> (f64, f64) {
+    let n = values.len() as f64;
+    let mean = values.iter().sum::<i64>() as f64 / n;
+    let variance = values
+        .iter()
+        .map(|value| (*value as f64 - mean).powi(2))
+        .sum::<f64>()
+        / (n - 1.0);
+    let degrees_of_freedom = values.len() - 1;
+    let t_critical = if degrees_of_freedom <= T_CRITICAL_VALUES_95.len() {
+        T_CRITICAL_VALUES_95[degrees_of_freedom - 1]
+    } else {
+        1.96
+    };
+    let margin = t_critical * (variance / n).sqrt();
+    (mean - margin, mean + margin)
+}
+
+/// Standard error of the slope of the `y = slope * x + intercept` fit.
+fn slope_standard_error(x: &[f64], y: &[f64], slope: f64, intercept: f64) -> f64 {
+    if x.len() < 3 {
+        return 0.0;
+    }
+    let mean_x = x.iter().sum::<f64>() / x.len() as f64;
+    let residual: f64 = x
+        .iter()
+        .zip(y)
+        .map(|(xi, yi)| (yi - (slope * xi + intercept)).powi(2))
+        .sum();
+    let variance_x: f64 = x.iter().map(|xi| (xi - mean_x).powi(2)).sum();
+    if variance_x == 0.0 {
+        0.0
+    } else {
+        (residual / (x.len() - 2) as f64).sqrt() / variance_x.sqrt()
+    }
+}
+
+/// Coefficient of determination of the `y = slope * x + intercept` fit.
+fn r_squared(x: &[f64], y: &[f64], slope: f64, intercept: f64) -> f64 {
+    let mean_y = y.iter().sum::<f64>() / y.len() as f64;
+    let total: f64 = y.iter().map(|yi| (yi - mean_y).powi(2)).sum();
+    let residual: f64 = x
+        .iter()
+        .zip(y)
+        .map(|(xi, yi)| (yi - (slope * xi + intercept)).powi(2))
+        .sum();
+    if total == 0.0 {
+        1.0
+    } else {
+        1.0 - residual / total
+    }
+}
+
+/// Lengths of the runs of consecutive weeks with a positive extra time.
+fn positive_week_streaks(weeks: &[WeeklySummary]) -> Vec<u32> {
+    let mut streaks = Vec::new();
+    let mut current_streak = 0;
+    let mut previous_week_start: Option<NaiveDate> = None;
+    for week in weeks {
+        let follows_previous_week =
+            previous_week_start == week.start.checked_sub_days(Days::new(7));
+        if week.extra_seconds > 0 {
+            if follows_previous_week || current_streak == 0 {
+                current_streak += 1;
+            } else {
+                streaks.push(current_streak);
+                current_streak = 1;
+            }
+        } else if current_streak > 0 {
+            streaks.push(current_streak);
+            current_streak = 0;
+        }
+        previous_week_start = Some(week.start);
+    }
+    if current_streak > 0 {
+        streaks.push(current_streak);
+    }
+    streaks
+}
+
+/// Rough burnout risk metric:
+///
+/// `(weeks_with_positive_streak * average_daily_extra_hours) / weekly_overtime_cap_hours`
+///
+/// where `weeks_with_positive_streak` counts the weeks belonging to a streak of
+/// at least two consecutive weeks with positive extra time, and `cap` is the
+/// weekly extra time, in seconds, considered sustainable. Above 1.0, the
+/// extra time is both sustained and high compared to the cap.
+fn burnout_index(streaks: &[u32], daily_extra: &[i64], cap: i64) -> f64 {
+    if daily_extra.is_empty() || cap <= 0 {
+        return 0.0;
+    }
+    let weeks_with_positive_streak: u32 = streaks.iter().filter(|streak| **streak >= 2).sum();
+    let average_daily_extra_hours =
+        daily_extra.iter().sum::<i64>() as f64 / daily_extra.len() as f64 / 3600.0;
+    weeks_with_positive_streak as f64 * average_daily_extra_hours / (cap as f64 / 3600.0)
+}
+
+/// Splits the range of `values` into `n_bins` bins of equal width and counts
+/// the values falling in each one, returns `(bin_start, bin_end, count)`.
+fn histogram(values: &[i64], n_bins: usize) -> Vec<(i64, i64, u32)> {
+    let (Some(min), Some(max)) = (values.iter().min(), values.iter().max()) else {
+        return Vec::new();
+    };
+    if n_bins == 0 {
+        return Vec::new();
+    }
+    let bin_width = ((max - min) as f64 / n_bins as f64).ceil().max(1.0) as i64;
+    let mut bins: Vec<(i64, i64, u32)> = (0..n_bins as i64)
+        .map(|i| (min + i * bin_width, min + (i + 1) * bin_width, 0))
+        .collect();
+    for value in values {
+        let index = (((value - min) / bin_width) as usize).min(n_bins - 1);
+        bins[index].2 += 1;
+    }
+    bins
+}
+
+/// Draws the bins as vertical bars, using eighth blocks for the top of each bar.
+fn render_histogram(
+    bins: &[(i64, i64, u32)],
+    height: usize,
+    format_bound: &dyn Fn(i64) -> String,
+) -> String {
+    const BLOCKS: [char; 9] = [' ', '▁', '▂', '▃', '▄', '▅', '▆', '▇', '█'];
+    let max_count = bins.iter().map(|bin| bin.2).max().unwrap_or(0).max(1);
+    let mut lines = Vec::new();
+    for row in (0..height).rev() {
+        let line: String = bins
+            .iter()
+            .map(|bin| {
+                let eighths = (bin.2 as usize * height * 8) / max_count as usize;
+                let block = BLOCKS[eighths.saturating_sub(row * 8).min(8)];
+                format!("{}{} ", block, block)
+            })
+            .collect();
+        lines.push(line.trim_end().to_string());
+    }
+    lines.push(
+        (1..=bins.len())
+            .map(|i| format!("{:<3}", i))
+            .collect::<String>()
+            .trim_end()
+            .to_string(),
+    );
+    for (i, (bin_start, bin_end, count)) in bins.iter().enumerate() {
+        lines.push(format!(
+            "{}: {} to {} ({} days)",
+            i + 1,
+            format_bound(*bin_start),
+            format_bound(*bin_end),
+            count
+        ));
+    }
+    lines.join("\n")
+}
+
+#[derive(Debug)]
+struct Streak {
+    start: NaiveDate,
+    end: NaiveDate,
+    length: usize,
+    total_extra_seconds: i64,
+}
+
+/// Runs of at least `min_length` consecutive working days of the sorted `days`
+/// whose extra time matches `predicate`.
+fn find_streaks(
+    days: &[NaiveDate],
+    extras: &HashMap<String, i64>,
+    min_length: usize,
+    predicate: impl Fn(i64) -> bool,
+) -> Vec<Streak> {
+    let mut streaks = Vec::new();
+    let mut current: Option<Streak> = None;
+    for day in days {
+        let extra = *extras.get(&day.format("%Y-%m-%d").to_string()).unwrap();
+        if !predicate(extra) {
+            if let Some(streak) = current.take()
+                && streak.length >= min_length
+            {
+                streaks.push(streak);
+            }
+            continue;
+        }
+        match current.as_mut() {
+            Some(streak) if nth_working_day_after(streak.end, 1) == *day => {
+                streak.end = *day;
+                streak.length += 1;
+                streak.total_extra_seconds += extra;
+            }
+            _ => {
+                if let Some(streak) = current.take()
+                    && streak.length >= min_length
+                {
+                    streaks.push(streak);
+                }
+                current = Some(Streak {
+                    start: *day,
+                    end: *day,
+                    length: 1,
+                    total_extra_seconds: extra,
+                });
+            }
+        }
+    }
+    if let Some(streak) = current
+        && streak.length >= min_length
+    {
+        streaks.push(streak);
+    }
+    streaks
+}
+
+fn find_deficit_streaks(
+    days: &[NaiveDate],
+    extras: &HashMap<String, i64>,
+    min_length: usize,
+) -> Vec<Streak> {
+    find_streaks(days, extras, min_length, |extra| extra < 0)
+}
+
+fn find_surplus_streaks(
+    days: &[NaiveDate],
+    extras: &HashMap<String, i64>,
+    min_length: usize,
+) -> Vec<Streak> {
+    find_streaks(days, extras, min_length, |extra| extra > 0)
+}
+
+/// Counts the days with (positive, negative, zero) extra time.
+fn count_overtime_days(extras: &HashMap<String, i64>) -> (u32, u32, u32) {
+    let mut counts = (0, 0, 0);
+    for extra in extras.values() {
+        match extra.signum() {
+            1 => counts.0 += 1,
+            -1 => counts.1 += 1,
+            _ => counts.2 += 1,
+        }
+    }
+    counts
+}
+
+/// Width of the y-axis labels drawn by the charts, separator included.
+const CHART_AXIS_WIDTH: usize = 9;
+
+/// Picks `columns` evenly spaced days out of `days`, or all of them if there
+/// are fewer days than columns.
+fn sample_days(days: &[NaiveDate], columns: usize) -> Vec<NaiveDate> {
+    if days.len() <= columns {
+        return days.to_vec();
+    }
+    (0..columns)
+        .map(|column| days[column * days.len() / columns])
+        .collect()
+}
+
+/// Y-axis label of a chart row, in hours formatted for `locale`.
+fn chart_axis_label(seconds: f64, locale: &Locale) -> String {
+    let hours = locale.format_decimal(seconds / 3600.0, 1);
+    let sign = if hours.starts_with('-') { "" } else { "+" };
+    format!("{:>6}h │", format!("{}{}", sign, hours))
+}
+
+/// The x-axis of a chart: a rule followed by the first and last dates.
+fn chart_x_axis(days: &[NaiveDate]) -> String {
+    let mut axis = format!(
+        "{}└{}",
+        " ".repeat(CHART_AXIS_WIDTH - 1),
+        "─".repeat(days.len())
+    );
+    if let (Some(first_day), Some(last_day)) = (days.first(), days.last()) {
+        let padding = days.len().saturating_sub(20).max(1);
+        axis.push_str(&format!(
+            "\n{}{}{}{}",
+            " ".repeat(CHART_AXIS_WIDTH),
+            first_day,
+            " ".repeat(padding),
+            last_day
+        ));
+    }
+    axis
+}
+
+/// Rows of a chart of `values`, with a y-axis. With `bars`, each value is
+/// drawn as a `█` bar from the zero line, otherwise as a `▲` point above the
+/// zero line `─` or a `▼` point below it.
+fn chart_rows(values: &[i64], height: u16, bars: bool, locale: &Locale) -> Vec<String> {
+    let height = height.max(2) as usize;
+    let max = values.iter().copied().max().unwrap_or(0).max(0) as f64;
+    let min = values.iter().copied().min().unwrap_or(0).min(0) as f64;
+    let step = ((max - min) / (height - 1) as f64).max(1.0);
+    let row_of = |value: f64| ((max - value) / step).round() as usize;
+    let zero_row = row_of(0.0);
+
+    let mut lines = Vec::new();
+    for row in 0..height {
+        let mut line = chart_axis_label(max - row as f64 * step, locale);
+        for value in values {
+            let value_row = row_of(*value as f64);
+            let in_bar =
+                (value_row <= row && row < zero_row) || (zero_row < row && row <= value_row);
+            line.push(if bars && in_bar {
+                '█'
+            } else if !bars && value_row == row && *value != 0 {
+                if *value > 0 { '▲' } else { '▼' }
+            } else if row == zero_row {
+                '─'
+            } else {
+                ' '
+            });
+        }
+        lines.push(line.trim_end().to_string());
+    }
+    lines
+}
+
+fn chart_values(days: &[NaiveDate], values_per_day: &HashMap<String, i64>) -> Vec<i64> {
+    days.iter()
+        .map(|day| {
+            *values_per_day
+                .get(&day.format("%Y-%m-%d").to_string())
+                .unwrap()
+        })
+        .collect()
+}
+
+/// Draws the extra time of each day as bars above or below the zero line.
+fn render_chart(
+    days: &[NaiveDate],
+    extras: &HashMap<String, i64>,
+    width: u16,
+    height: u16,
+    locale: &Locale,
+) -> String {
+    let days = sample_days(
+        days,
+        (width as usize).saturating_sub(CHART_AXIS_WIDTH).max(1),
+    );
+    let mut lines = chart_rows(&chart_values(&days, extras), height, true, locale);
+    lines.push(chart_x_axis(&days));
+    lines.join("\n")
+}
+
+/// Plots the cumulated balance of each day, `▲` above the zero line `─` and
+/// `▼` below it.
+fn render_cumulative_chart(
+    days: &[NaiveDate],
+    cumulative: &HashMap<String, i64>,
+    width: u16,
+    height: u16,
+    locale: &Locale,
+) -> String {
+    let days = sample_days(
+        days,
+        (width as usize).saturating_sub(CHART_AXIS_WIDTH).max(1),
+    );
+    let mut lines = chart_rows(&chart_values(&days, cumulative), height, false, locale);
+    lines.push(chart_x_axis(&days));
+    lines.join("\n")
+}
+
+/// Draws the daily extra time above the cumulated balance, both charts
+/// sharing the same dates on their x-axis.
+fn render_dual_chart(
+    days: &[NaiveDate],
+    extras: &HashMap<String, i64>,
+    cumulative: &HashMap<String, i64>,
+    width: u16,
+    height: u16,
+    locale: &Locale,
+) -> String {
+    let panel_height = (height / 2).max(2);
+    let daily_chart = render_chart(days, extras, width, panel_height, locale);
+    let mut lines = vec![String::from("Daily extra time")];
+    lines.extend(
+        daily_chart
+            .lines()
+            .take(panel_height as usize)
+            .map(|line| line.to_string()),
+    );
+    lines.push(String::from("Cumulated extra time"));
+    lines.push(render_cumulative_chart(
+        days,
+        cumulative,
+        width,
+        panel_height,
+        locale,
+    ));
+    lines.join("\n")
+}
+
+/// Shows the daily extra time in a full terminal chart. The left and right
+/// arrows move the selected day, whose entries are listed under the chart, and
+/// 'q' or Escape quits.
+fn run_interactive_chart(
+    days: &[NaiveDate],
+    extras: &HashMap<String, i64>,
+    entries_per_day: &HashMap<String, Vec<Entry>>,
+    show_duration: &dyn Fn(i64) -> String,
+    show_extra: &dyn Fn(i64) -> String,
+    locale: &Locale,
+) -> std::io::Result<()> {
+    use ratatui::crossterm::event::{self, Event, KeyCode, KeyEventKind};
+    use ratatui::layout::{Constraint, Layout};
+    use ratatui::widgets::{Block, Paragraph};
+
+    let mut selected = days.len().saturating_sub(1);
+    ratatui::run(|terminal| {
+        loop {
+            terminal.draw(|frame| {
+                let [chart_area, details_area] =
+                    Layout::vertical([Constraint::Min(8), Constraint::Length(10)])
+                        .areas(frame.area());
+
+                let columns = (chart_area.width as usize)
+                    .saturating_sub(CHART_AXIS_WIDTH + 2)
+                    .max(1);
+                let first_visible = (selected + 1).saturating_sub(columns);
+                let visible_days = &days[first_visible..days.len().min(first_visible + columns)];
+                let chart_height = chart_area.height.saturating_sub(3).max(2);
+                let mut lines = chart_rows(
+                    &chart_values(visible_days, extras),
+                    chart_height,
+                    true,
+                    locale,
+                );
+                lines.push(format!(
+                    "{}^",
+                    " ".repeat(CHART_AXIS_WIDTH + selected - first_visible)
+                ));
+                frame.render_widget(
+                    Paragraph::new(lines.join("\n")).block(
+                        Block::bordered().title(" Daily extra time, ←/→ to move, q to quit "),
+                    ),
+                    chart_area,
+                );
+
+                let day = days[selected].format("%Y-%m-%d").to_string();
+                let mut entries = entries_per_day.get(&day).cloned().unwrap_or_default();
+                entries.sort_by_key(|entry| entry.start);
+                let details: Vec<String> = entries
+                    .iter()
+                    .map(|entry| {
+                        format!(
+                            "{}–{}  {:>10}  {}",
+                            entry.start.format("%H:%M"),
+                            entry.end().format("%H:%M"),
+                            show_duration(entry.duration),
+                            entry.description
+                        )
+                    })
+                    .collect();
+                frame.render_widget(
+                    Paragraph::new(details.join("\n")).block(Block::bordered().title(format!(
+                        " {}, extra time {} ",
+                        day,
+                        show_extra(*extras.get(&day).unwrap_or(&0))
+                    ))),
+                    details_area,
+                );
+            })?;
+            if let Event::Key(key) = event::read()?
+                && key.kind == KeyEventKind::Press
+            {
+                match key.code {
+                    KeyCode::Char('q') | KeyCode::Esc => return Ok(()),
+                    KeyCode::Left | KeyCode::Char('h') => selected = selected.saturating_sub(1),
+                    KeyCode::Right | KeyCode::Char('l') => {
+                        selected = (selected + 1).min(days.len() - 1)
+                    }
+                    KeyCode::Home => selected = 0,
+                    KeyCode::End => selected = days.len() - 1,
+                    _ => {}
+                }
+            }
+        }
+    })
+}
+
+/// First and last day of a quarter of a fiscal year starting on the first day
+/// of `fiscal_start_month`. Fiscal year `year` starts in `year`, so with an
+/// April start its fourth quarter is January to March of `year + 1`. Returns
+/// None when `quarter` is not between 1 and 4 or the dates can not be represented.
+fn quarter_bounds(
+    year: i32,
+    quarter: u8,
+    fiscal_start_month: u8,
+) -> Option<(NaiveDate, NaiveDate)> {
+    if !(1..=4).contains(&quarter) {
+        return None;
+    }
+    let start = NaiveDate::from_ymd_opt(year, fiscal_start_month as u32, 1)?
+        .checked_add_months(Months::new(3 * (quarter as u32 - 1)))?;
+    let end = start.checked_add_months(Months::new(3))?.pred_opt()?;
+    Some((start, end))
+}
+
+/// First and last day of the full week before the one of `today`.
+fn last_week_range(today: NaiveDate, week_start: Weekday) -> (NaiveDate, NaiveDate) {
+    let start = today
+        .week(week_start)
+        .first_day()
+        .checked_sub_days(Days::new(7))
+        .unwrap();
+    (start, start.checked_add_days(Days::new(6)).unwrap())
+}
+
+/// First and last day of the month before the one of `today`.
+fn last_month_range(today: NaiveDate) -> (NaiveDate, NaiveDate) {
+    let this_month_start = today.with_day(1).unwrap();
+    let start = this_month_start.checked_sub_months(Months::new(1)).unwrap();
+    (start, this_month_start.pred_opt().unwrap())
+}
+
+/// First and last day of the quarter before the one of `today`, quarters
+/// starting every three months from `fiscal_start_month`.
+fn previous_quarter(today: NaiveDate, fiscal_start_month: u8) -> (NaiveDate, NaiveDate) {
+    let months_into_quarter = (today.month() + 12 - fiscal_start_month as u32) % 3;
+    let quarter_start = today
+        .with_day(1)
+        .unwrap()
+        .checked_sub_months(Months::new(months_into_quarter))
+        .unwrap();
+    let start = quarter_start.checked_sub_months(Months::new(3)).unwrap();
+    (start, quarter_start.pred_opt().unwrap())
+}
+
+/// Returns the (shortest, longest) days, by time worked. `all_days` must not be empty.
+fn find_extreme_days(
+    all_days: &[String],
+    work_duration_in_seconds_per_day: &HashMap<String, i64>,
+) -> (String, String) {
+    let shortest = all_days
+        .iter()
+        .min_by_key(|day| work_duration_in_seconds_per_day.get(*day).unwrap())
+        .unwrap();
+    let longest = all_days
+        .iter()
+        .max_by_key(|day| work_duration_in_seconds_per_day.get(*day).unwrap())
+        .unwrap();
+    (shortest.clone(), longest.clone())
+}
+
+/// Returns the (day, duration, occurrences) of the durations found several
+/// times in the same day, sorted by day then duration.
+fn find_duplicate_durations(tasks: &HashMap<String, Vec<i64>>) -> Vec<(String, i64, usize)> {
+    let mut duplicates = Vec::new();
+    for (day, durations) in tasks {
+        let mut occurrences: HashMap<i64, usize> = HashMap::new();
+        for duration in durations {
+            *occurrences.entry(*duration).or_default() += 1;
+        }
+        for (duration, count) in occurrences {
+            if count > 1 {
+                duplicates.push((day.clone(), duration, count));
+            }
+        }
+    }
+    duplicates.sort();
+    duplicates
+}
+
+/// Ranks the days from the most extra time (rank 1) to the least, days with
+/// the same extra time sharing the same rank.
+fn daily_ranks(extra_per_day: &HashMap<String, i64>) -> HashMap<String, usize> {
+    let mut sorted: Vec<(&String, &i64)> = extra_per_day.iter().collect();
+    sorted.sort_by(|a, b| b.1.cmp(a.1).then(a.0.cmp(b.0)));
+    let mut ranks = HashMap::new();
+    let mut rank = 0;
+    for (i, (day, extra)) in sorted.iter().enumerate() {
+        if i == 0 || *sorted[i - 1].1 != **extra {
+            rank = i + 1;
+        }
+        ranks.insert((*day).clone(), rank);
+    }
+    ranks
+}
+
+fn main() {
+    let opt = Opt::from_args();
+
+    if opt.schema {
+        let schema = schemars::schema_for!(ExtraTimeReport);
+        println!("{}", serde_json::to_string_pretty(&schema).unwrap());
+        return;
+    }
+
+    if let Some(report_path) = &opt.verify_checksum {
+        let report_text = std::fs::read_to_string(report_path).expect("Could not read JSON file");
+        let report: Value = serde_json::from_str(&report_text).expect("File is not valid JSON");
+        let Some(checksum) = report["checksum"].as_str() else {
+            eprintln!("{} has no checksum", report_path.display());
+            std::process::exit(1);
+        };
+        if report_checksum(&report) != checksum {
+            eprintln!("Checksum mismatch, {} was modified", report_path.display());
+            std::process::exit(1);
+        }
+        println!("Checksum OK");
+        return;
+    }
+
+    // No token is needed when the answers come from a fixture
+    let token = opt
+        .token
+        .clone()
+        .or_else(|| opt.mock_api.as_ref().map(|_| String::from("mock")));
+    // Everything but the daily json objects goes to stderr with --ndjson, so
+    // that stdout only carries the stream
+    macro_rules! report {
+        ($($arg:tt)*) => {
+            if opt.ndjson {
+                eprintln!($($arg)*)
+            } else {
+                println!($($arg)*)
+            }
+        };
+    }
+
+    if let Some(token) = token {
+        let token = token.as_str();
+        let debug = opt.debug;
+        let include_today = opt.include_today;
+        let human_readable = opt.human_readable;
+        let emoji = opt.emoji && std::env::var_os("NO_COLOR").is_none();
+        // Every duration shown goes through display_as, `plain` being its
+        // format without --human-readable
+        let display_as = |seconds: i64, balance: bool, plain: fn(i64) -> String| {
+            let duration = if human_readable {
+                humanise_duration(seconds, balance)
+            } else {
+                plain(seconds)
+            };
+            if emoji {
+                format!("⏱ {}", duration)
+            } else {
+                duration
+            }
+        };
+        let display_duration = |seconds: i64| display_as(seconds, false, format_duration);
+        let display_balance = |seconds: i64| display_as(seconds, true, format_duration);
+        let display_hours_minutes = |seconds: i64| display_as(seconds, false, format_hours_minutes);
+        let display_extra = |seconds: i64| display_as(seconds, true, format_signed_hours_minutes);
+        let locale = match &opt.locale {
+            Some(name) => Locale::from_name(name),
+            None => Locale::system(),
+        };
+        // Files keep plain numbers unless a locale is asked for, so that they
+        // can be read back by programs
+        let csv_locale = match &opt.locale {
+            Some(_) => locale.clone(),
+            None => Locale::from_name("C"),
+        };
+        let (delimiter, default_file_name) = if opt.tsv {
+            ('\t', "results.tsv")
+        } else if opt.psv {
+            ('|', "results.psv")
+        } else {
+            (opt.delimiter, "results.csv")
+        };
+        let csv_file_name = opt.output.as_deref().unwrap_or(default_file_name);
+        let mut sheet = CSVSheet::new(csv_file_name, delimiter, csv_locale.clone());
+
+        if let (Some(start_at), Some(end_at)) = (opt.start_at, opt.end_at)
+            && end_at <= start_at
+        {
+            eprintln!("--end-at must be after --start-at");
+            std::process::exit(1);
+        }
+        let fields: Option<Vec<&str>> = opt
+            .fields
+            .as_deref()
+            .map(|fields| fields.split(',').map(str::trim).collect());
+        if let Some(fields) = &fields
+            && let Some(unknown) = fields
+                .iter()
+                .find(|field| !DAILY_ENTRY_FIELDS.contains(field))
+        {
+            eprintln!(
+                "Unknown field {}, valid fields are: {}",
+                unknown,
+                DAILY_ENTRY_FIELDS.join(", ")
+            );
+            std::process::exit(1);
+        }
+        let renames: Option<Vec<(&str, &str)>> = opt.rename_fields.as_deref().map(|renames| {
+            renames
+                .split(',')
+                .map(|rename| match rename.split_once('=') {
+                    Some((old_name, new_name)) => (old_name.trim(), new_name.trim()),
+                    None => {
+                        eprintln!("--rename-fields expects old=new pairs, got {}", rename);
+                        std::process::exit(1);
+                    }
+                })
+                .collect()
+        });
+        // Without --time-zone nor --timezone-offset the time zone of the system
+        // is used, days following the API offset when it can not be detected
+        let time_zone: Option<chrono_tz::Tz> = match (opt.time_zone, opt.timezone_offset) {
+            (Some(time_zone), _) => Some(time_zone),
+            (None, Some(_)) => None,
+            (None, None) => {
+                let detected = iana_time_zone::get_timezone()
+                    .ok()
+                    .and_then(|name| name.parse::<chrono_tz::Tz>().ok());
+                match detected {
+                    Some(time_zone) => {
+                        if debug {
+                            report!("Detected time zone: {}", time_zone.name());
+                        }
+                        Some(time_zone)
+                    }
+                    None if opt.detect_timezone => {
+                        eprintln!("Could not detect the time zone of the system");
+                        std::process::exit(1);
+                    }
+                    None => None,
+                }
+            }
+        };
+        // Days start at midnight in this time zone
+        let to_day_time_zone = |time: DateTime<FixedOffset>| match (opt.timezone_offset, time_zone)
+        {
+            (Some(offset), _) => time.with_timezone(&offset),
+            (None, Some(time_zone)) => time.with_timezone(&time_zone).fixed_offset(),
+            (None, None) => time,
+        };
+        let current_time = to_day_time_zone(chrono::offset::Local::now().fixed_offset());
+        let (query_start_time, window_description) =
+            match (opt.weeks_since_start, opt.years_since_start) {
+                (Some(weeks), _) => (
+                    weeks
+                        .checked_mul(7)
+                        .and_then(|days| current_time.checked_sub_days(Days::new(days))),
+                    format!("{}-week", weeks),
+                ),
+                (None, Some(years)) => (
+                    years
+                        .checked_mul(12)
+                        .and_then(|months| current_time.checked_sub_months(Months::new(months))),
+                    format!("{}-year", years),
+                ),
+                (None, None) => {
+                    let months = opt.months_since_start.unwrap_or(DEFAULT_MONTHS_SINCE_START);
+                    (
+                        current_time.checked_sub_months(Months::new(months)),
+                        format!("{}-month", months),
+                    )
+                }
+            };
+        let Some(query_start_time) = query_start_time else {
+            eprintln!(
+                "The {} window starts too far in the past",
+                window_description
+            );
+            std::process::exit(1);
+        };
+        let mut query_start = query_start_time.date_naive();
+        let mut query_end = if include_today {
+            current_time.date_naive()
+        } else {
+            current_time
+                .checked_sub_days(Days::new(1))
+                .unwrap()
+                .date_naive()
+        };
+        // A fixed period replaces the default window, without going past its end
+        let fiscal_start_month = opt.fiscal_year_start.unwrap_or(1);
+        if !(1..=12).contains(&fiscal_start_month) {
+            eprintln!("--fiscal-year-start must be a month between 1 and 12");
+            std::process::exit(1);
+        }
+        let fixed_period = match (opt.quarter, opt.week, opt.month, opt.year) {
+            (Some(quarter), _, _, Some(year)) => {
+                match quarter_bounds(year, quarter, fiscal_start_month) {
+                    Some(bounds) => Some(bounds),
+                    None => {
+                        eprintln!("{} has no quarter {}", year, quarter);
+                        std::process::exit(1);
+                    }
+                }
+            }
+            (None, Some(week), _, Some(year)) => {
+                match NaiveDate::from_isoywd_opt(year, week, Weekday::Mon) {
+                    Some(monday) => Some((monday, monday.checked_add_days(Days::new(6)).unwrap())),
+                    None => {
+                        eprintln!("{} has no ISO week {}", year, week);
+                        std::process::exit(1);
+                    }
+                }
+            }
+            (None, None, Some(month), Some(year)) => {
+                let Some(first_day) = NaiveDate::from_ymd_opt(year, month, 1) else {
+                    eprintln!("{} has no month {}", year, month);
+                    std::process::exit(1);
+                };
+                let last_day = (28..=31)
+                    .rev()
+                    .find_map(|day| first_day.with_day(day))
+                    .unwrap();
+                Some((first_day, last_day))
+            }
+            (None, None, None, Some(_)) => {
+                eprintln!("--year needs --quarter, --week or --month");
+                std::process::exit(1);
+            }
+            _ if opt.ytd => {
+                let today = current_time.date_naive();
+                let mut year_start =
+                    NaiveDate::from_ymd_opt(today.year(), fiscal_start_month as u32, 1).unwrap();
+                if year_start > today {
+                    year_start = year_start.with_year(today.year() - 1).unwrap();
+                }
+                let start = first_working_day_from(year_start);
+                if !opt.ndjson {
+                    report!("Year to date since {}", start);
+                }
+                Some((start, query_end))
+            }
+            _ if opt.mtd => {
+                let today = current_time.date_naive();
+                let start = first_working_day_from(today.with_day(1).unwrap());
+                if start > query_end {
+                    eprintln!(
+                        "No working day of the month before {}, nothing to compute yet",
+                        query_end.succ_opt().unwrap()
+                    );
+                    return;
+                }
+                Some((start, query_end))
+            }
+            _ if opt.wtd => {
+                let today = current_time.date_naive();
+                let start = today.week(opt.week_start).first_day();
+                if start > query_end {
+                    eprintln!("The week started on {}, nothing to compute yet", start);
+                    return;
+                }
+                Some((start, query_end))
+            }
+            _ if opt.last_week => Some(last_week_range(current_time.date_naive(), opt.week_start)),
+            _ if opt.last_month => Some(last_month_range(current_time.date_naive())),
+            _ if opt.last_quarter => Some(previous_quarter(
+                current_time.date_naive(),
+                fiscal_start_month,
+            )),
+            _ => None,
+        };
+        if let Some((period_start, period_end)) = fixed_period {
+            if period_start > query_end {
+                eprintln!(
+                    "The period {} to {} is in the future",
+                    period_start, period_end
+                );
+                std::process::exit(1);
+            }
+            query_start = period_start;
+            query_end = period_end.min(query_end);
+        }
+        if let Some(first_working_day) = opt.first_working_day
+            && first_working_day > query_start
+        {
+            query_start = first_working_day;
+        }
+        if query_start > query_end {
+            eprintln!(
+                "The first working day {} is after {}, nothing to compute",
+                query_start, query_end
+            );
+            return;
+        }
+        if let Some(last_working_day) = opt.last_working_day
+            && last_working_day < query_end
+        {
+            query_end = last_working_day;
+        }
+        if query_end < query_start {
+            eprintln!(
+                "The last working day {} is before {}, nothing to compute",
+                query_end, query_start
+            );
+            return;
+        }
+
+        let mut total_work_duration_per_day: HashMap<String, i64> = HashMap::new();
+        let mut all_days = Vec::new();
+
+        if !opt.ndjson {
+            report!(
+                "Computing extra time worked between {} and {}",
+                query_start,
+                query_end
+            );
+        }
+
+        // A single request can not cover more than API_MAX_TIME_PER_REQUEST,
+        // longer periods are split into several requests
+        let mut query_windows = Vec::new();
+        let mut window_start = query_start;
+        loop {
+            let window_end = window_start
+                .checked_add_months(API_MAX_TIME_PER_REQUEST)
+                .unwrap()
+                .min(query_end);
+            query_windows.push((window_start, window_end));
+            if window_end >= query_end {
+                break;
+            }
+            window_start = window_end;
+        }
+        if debug {
+            report!(
+                "Issuing {} API requests to cover the {} window",
+                query_windows.len(),
+                window_description
+            );
+        }
+
+        let toggl = TogglClient::new(token, &opt);
+        let mut all_tasks: Vec<Value> = Vec::new();
+        let mut fetched_ids = HashSet::new();
+        for (window_start, window_end) in query_windows {
+            let url_to_query = format!(
+                "{}/me/time_entries?start_date={}&end_date={}",
+                TOGGL_API_URL, window_start, window_end
+            );
+
+            // The token travels as basic auth and is never shown
+            if opt.print_query_url {
+                report!("{} (basic auth: <redacted>:api_token)", url_to_query);
+                continue;
+            }
+
+            if !opt.ndjson {
+                report!("Querying url: {}", url_to_query);
+            }
+
+            // Entries lying on the boundary of two windows are only kept once
+            for task in toggl.fetch_time_entries(&url_to_query) {
+                let Some(id) = task["id"].as_i64() else {
+                    eprintln!("Time entry without an id: {}", task);
+                    std::process::exit(1);
+                };
+                if fetched_ids.insert(id) {
+                    all_tasks.push(task);
+                }
+            }
+        }
+        if opt.print_query_url {
+            return;
+        }
+
+        let mut tasks_per_day: HashMap<String, Vec<i64>> = HashMap::new();
+        let mut entries_per_day: HashMap<String, Vec<Entry>> = HashMap::new();
+
+        let mut skipped_midnight_entries = 0;
+        let mut skipped_midnight_duration = 0;
+        for task in all_tasks.into_iter() {
+            let start = to_day_time_zone(
+                DateTime::parse_from_rfc3339(task["start"].as_str().unwrap()).unwrap(),
+            );
+            // Entries started at midnight usually come from a timer left running
+            if opt.ignore_midnight_entries
+                && start.time() == NaiveTime::from_hms_opt(0, 0, 0).unwrap()
+            {
+                skipped_midnight_entries += 1;
+                skipped_midnight_duration += task["duration"].as_i64().unwrap();
+                continue;
+            }
+            let day = start.date_naive();
+            if let Some(first_working_day) = opt.first_working_day
+                && day < first_working_day
+            {
+                continue;
+            }
+            if let Some(last_working_day) = opt.last_working_day
+                && day > last_working_day
+            {
+                continue;
+            }
+            let day_as_string = day.format("%Y-%m-%d").to_string();
+
+            let mut entry = Entry {
+                id: task["id"].as_i64().unwrap(),
+                start,
+                duration: task["duration"].as_i64().unwrap(),
+                description: task["description"].as_str().unwrap_or("").to_string(),
+                project_id: task["project_id"].as_i64(),
+                tags: task["tags"]
+                    .as_array()
+                    .into_iter()
+                    .flatten()
+                    .filter_map(|tag| tag.as_str())
+                    .map(|tag| tag.to_string())
+                    .collect(),
+                billable: task["billable"].as_bool().unwrap_or(false),
+            };
+            // Only the part of the entry inside the workday window is kept
+            if let (Some(start_at), Some(end_at)) = (opt.start_at, opt.end_at) {
+                let window_start = day
+                    .and_time(start_at)
+                    .and_local_timezone(*entry.start.offset())
+                    .unwrap();
+                entry.duration = clip_entry_to_window(&entry, start_at, end_at);
+                entry.start = entry.start.max(window_start);
+            }
+            // Times of the entries are shown in their own time zone when one is set
+            if let Some(entry_timezone) = opt.entry_timezone {
+                entry.start = entry.start.with_timezone(&entry_timezone).fixed_offset();
+            }
+            let worktime_in_seconds = entry.duration;
+            entries_per_day
+                .entry(day_as_string.clone())
+                .or_default()
+                .push(entry);
+
+            if tasks_per_day.contains_key(&day_as_string) {
+                let mut current_tasks = tasks_per_day.get(&day_as_string).unwrap().clone();
+                current_tasks.push(worktime_in_seconds);
+                tasks_per_day.remove(&day_as_string);
+                tasks_per_day.insert(day_as_string.clone(), current_tasks);
+            } else {
+                tasks_per_day.insert(day_as_string.clone(), Vec::from([worktime_in_seconds]));
+                all_days.push(day_as_string);
+            }
+        }
+
+        all_days.sort();
+
+        if opt.ignore_midnight_entries && !opt.ndjson {
+            report!(
+                "Ignored {} entries started at midnight, {}",
+                skipped_midnight_entries,
+                display_duration(skipped_midnight_duration)
+            );
+        }
+
+        // Fetched once, the client names of the exported entries reuse them
+        let projects = if opt.show_entry_projects || opt.export_entries.is_some() {
+            toggl.fetch_me("projects")
+        } else {
+            Value::Null
+        };
+        let project_names = project_names(&projects);
+
+        for day in &all_days {
+            let day = day.clone();
+            let entries = entries_per_day.get(&day).unwrap();
+            let mut column_to_add_in_sheet = Vec::from([day.clone()]);
+            let mut total_worked_that_day = 0;
+            for entry in entries.iter() {
+                total_worked_that_day += entry.duration;
+                if opt.hide_tasks {
+                    continue;
+                }
+                column_to_add_in_sheet.push(csv_locale.format_integer(entry.duration));
+                if opt.show_entry_ids {
+                    column_to_add_in_sheet.push(format!("Entry ID: {}", entry.id));
+                }
+                if opt.show_entry_descriptions {
+                    if entry.description.is_empty() {
+                        column_to_add_in_sheet.push(String::from("(no description)"));
+                    } else {
+                        column_to_add_in_sheet.push(entry.description.clone());
+                    }
+                }
+                if opt.show_entry_projects {
+                    column_to_add_in_sheet.push(match entry.project_id {
+                        Some(project_id) => match project_names.get(&project_id) {
+                            Some(name) => name.clone(),
+                            None => format!("Project {}", project_id),
+                        },
+                        None => String::from("(No Project)"),
+                    });
+                }
+                if opt.show_entry_tags {
+                    if entry.tags.is_empty() {
+                        column_to_add_in_sheet.push(String::from("(No Tags)"));
+                    } else {
+                        column_to_add_in_sheet.push(entry.tags.join(","));
+                    }
+                }
+            }
+            sheet.add_column(column_to_add_in_sheet);
+            total_work_duration_per_day.insert(day.clone(), total_worked_that_day);
+        }
+
+        // With --include-weekends nothing is expected on weekends, so any time
+        // logged then is overtime
+        let expected_time = |day: &str| {
+            let date = NaiveDate::parse_from_str(day, "%Y-%m-%d").unwrap();
+            if opt.include_weekends && !is_working_day(date) {
+                0
+            } else {
+                NORMAL_WORKING_TIME_PER_DAY_IN_SECONDS
+            }
+        };
+        let mut total_extra_time_worked: i64 = 0;
         let mut cumulated_extra_time_per_day: HashMap<String, i64> = HashMap::new();
+        let mut extra_time_per_day: HashMap<String, i64> = HashMap::new();
         for day in &all_days {
             let time_worked_this_day = total_work_duration_per_day.get(day).unwrap();
-            let extra_time_worked = time_worked_this_day - NORMAL_WORKING_TIME_PER_DAY_IN_SECONDS;
+            let expected_time_this_day = expected_time(day);
+            let extra_time_worked = time_worked_this_day - expected_time_this_day;
             total_extra_time_worked += extra_time_worked;
+            extra_time_per_day.insert(day.clone(), extra_time_worked);
             cumulated_extra_time_per_day.insert(day.clone(), total_extra_time_worked);
             if debug {
-                println!("Extra time worked at day {}: {}", day, extra_time_worked);
+                report!("Extra time worked at day {}: {}", day, extra_time_worked);
+            }
+            if opt.ndjson {
+                let daily_entry = DailyEntry {
+                    date: day.clone(),
+                    worked: *time_worked_this_day,
+                    expected: expected_time_this_day,
+                    extra: extra_time_worked,
+                    cumulative: total_extra_time_worked,
+                };
+                println!("{}", serde_json::to_string(&daily_entry).unwrap());
+            }
+        }
+
+        let row_separator = if opt.separator.is_empty() {
+            None
+        } else {
+            Some(opt.separator.as_str())
+        };
+
+        if opt.compact {
+            let rows: Vec<[String; 3]> = all_days
+                .iter()
+                .map(|day| {
+                    [
+                        format_hms(*total_work_duration_per_day.get(day).unwrap(), false),
+                        format_hms(*extra_time_per_day.get(day).unwrap(), true),
+                        format!(
+                            "({})",
+                            format_hms(*cumulated_extra_time_per_day.get(day).unwrap(), true)
+                        ),
+                    ]
+                })
+                .collect();
+            // Columns are as wide as their widest value, which grows past 99 hours
+            let width = |column: usize| rows.iter().map(|row| row[column].len()).max().unwrap_or(0);
+            let widths = [width(0), width(1), width(2)];
+            for (i, (day, [worked, extra, cumulative])) in all_days.iter().zip(&rows).enumerate() {
+                let line = format!(
+                    "{}  {:>w0$}  {:>w1$}  {:>w2$}",
+                    day,
+                    worked,
+                    extra,
+                    cumulative,
+                    w0 = widths[0],
+                    w1 = widths[1],
+                    w2 = widths[2]
+                );
+                if let Some(separator) = row_separator
+                    && i > 0
+                {
+                    report!("{}", separator_line(separator, line.chars().count()));
+                }
+                report!("{}", line);
+            }
+        }
+
+        if opt.wide {
+            let headers: Vec<String> = [
+                "Date",
+                "Entries",
+                "Worked",
+                "Expected",
+                "Extra",
+                "Cumulated",
+                "Percent",
+                "First entry",
+                "Last entry",
+            ]
+            .iter()
+            .map(|header| header.to_string())
+            .collect();
+            let mut rows = Vec::new();
+            for day in &all_days {
+                let worked = *total_work_duration_per_day.get(day).unwrap();
+                let expected = expected_time(day);
+                let entries = entries_per_day.get(day).unwrap();
+                let first_entry = entries.iter().map(|entry| entry.start).min().unwrap();
+                let last_entry = entries.iter().map(|entry| entry.end()).max().unwrap();
+                rows.push(vec![
+                    day.clone(),
+                    entries.len().to_string(),
+                    format_hms(worked, false),
+                    format_hms(expected, false),
+                    format_hms(worked - expected, true),
+                    format_hms(*cumulated_extra_time_per_day.get(day).unwrap(), true),
+                    if expected > 0 {
+                        format!(
+                            "{}%",
+                            locale.format_decimal(worked as f64 * 100.0 / expected as f64, 0)
+                        )
+                    } else {
+                        String::from("-")
+                    },
+                    first_entry.format("%H:%M").to_string(),
+                    last_entry.format("%H:%M").to_string(),
+                ]);
+            }
+            for line in format_table(&headers, &rows, terminal_width(), row_separator) {
+                report!("{}", line);
             }
         }
 
-        if opt.csv {
-            sheet.sort_columns();
-            sheet.add_total_times_to_columns(&total_work_duration_per_day, &cumulated_extra_time_per_day);
+        if opt.csv || opt.tsv || opt.psv {
+            let sort_sheet = |sheet: &mut CSVSheet| {
+                if opt.sort_columns_by_total_desc {
+                    sheet.sort_columns_by_total_desc(&total_work_duration_per_day);
+                } else {
+                    sheet.sort_columns();
+                }
+            };
+            let mut sheet = if opt.totals_only {
+                let mut totals_sheet = CSVSheet::new(csv_file_name, delimiter, csv_locale.clone());
+                for day in &all_days {
+                    totals_sheet.add_column(Vec::from([
+                        day.clone(),
+                        csv_locale.format_integer(*total_work_duration_per_day.get(day).unwrap()),
+                        csv_locale.format_integer(*cumulated_extra_time_per_day.get(day).unwrap()),
+                    ]));
+                }
+                sort_sheet(&mut totals_sheet);
+                totals_sheet.align_columns();
+                totals_sheet
+            } else {
+                sort_sheet(&mut sheet);
+                if opt.no_totals {
+                    sheet.align_columns();
+                } else {
+                    sheet.add_total_times_to_columns(
+                        &total_work_duration_per_day,
+                        &extra_time_per_day,
+                        &cumulated_extra_time_per_day,
+                    );
+                }
+                sheet
+            };
+            if opt.show_daily_rank {
+                sheet.add_rank_to_columns(&daily_ranks(&extra_time_per_day));
+            }
+            if opt.pad_missing_days {
+                for day in missing_working_days(&all_days) {
+                    sheet.add_column(Vec::from([day]));
+                }
+                sort_sheet(&mut sheet);
+                sheet.align_columns();
+            }
+            if opt.period_summary {
+                add_period_summary_column(
+                    &mut sheet,
+                    &all_days,
+                    &total_work_duration_per_day,
+                    &extra_time_per_day,
+                );
+            }
+            if opt.start_end_columns {
+                sheet.add_metadata_column(
+                    "Start",
+                    Vec::from([
+                        (String::from("Start date :"), query_start.to_string()),
+                        (String::from("Token :"), mask_token(token)),
+                        (
+                            String::from("Working hours per day :"),
+                            csv_locale.format_decimal(
+                                NORMAL_WORKING_TIME_PER_DAY_IN_SECONDS as f64 / 3600.0,
+                                2,
+                            ),
+                        ),
+                    ]),
+                    true,
+                );
+                sheet.add_metadata_column(
+                    "End",
+                    Vec::from([
+                        (String::from("End date :"), query_end.to_string()),
+                        (
+                            String::from("Final balance :"),
+                            csv_locale.format_integer(total_extra_time_worked),
+                        ),
+                    ]),
+                    false,
+                );
+            }
+            if opt.include_weekends {
+                sheet.label_weekend_columns();
+            }
+            sheet.dialect = opt.csv_dialect;
+            sheet.line_ending = opt.line_ending;
+            sheet.bom = opt.bom;
             sheet.write_csv_file();
         }
 
-        let extra_time_worked: TimeDelta = TimeDelta::seconds(total_extra_time_worked);
+        if opt.json {
+            let mut report = ExtraTimeReport::new(
+                query_start,
+                query_end,
+                &all_days,
+                &total_work_duration_per_day,
+                &extra_time_per_day,
+                &cumulated_extra_time_per_day,
+            );
+            if opt.add_metadata {
+                report.metadata = Some(ReportMetadata::new(query_start, query_end));
+            }
+            let indent = if opt.json_pretty {
+                Some(opt.json_indent)
+            } else {
+                None
+            };
+            let mut output = if let Some(fields) = &fields {
+                report.to_json_with_fields(fields, opt.json_flat)
+            } else if opt.json_flat {
+                report.to_flat_json()
+            } else {
+                serde_json::to_value(&report).unwrap()
+            };
+            if let Some(renames) = &renames {
+                for old_name in rename_fields(&mut output, renames) {
+                    eprintln!("Warning: no field {} to rename in the json file", old_name);
+                }
+            }
+            if opt.checksum {
+                let checksum = report_checksum(&output);
+                output["checksum"] = Value::from(checksum);
+            }
+            write_json_file("results.json", &output, indent);
+            if let Some(schema_path) = &opt.validate_output {
+                let violations = validate_json_file("results.json", schema_path);
+                if !violations.is_empty() {
+                    eprintln!("results.json does not match {}:", schema_path.display());
+                    for violation in violations {
+                        eprintln!("  - {}", violation);
+                    }
+                    std::process::exit(1);
+                }
+            }
+        }
+
+        if opt.excel {
+            let report = ExtraTimeReport::new(
+                query_start,
+                query_end,
+                &all_days,
+                &total_work_duration_per_day,
+                &extra_time_per_day,
+                &cumulated_extra_time_per_day,
+            );
+            write_excel_file("results.xlsx", &report, opt.excel_chart);
+        }
+
+        if opt.google_sheets {
+            let report = ExtraTimeReport::new(
+                query_start,
+                query_end,
+                &all_days,
+                &total_work_duration_per_day,
+                &extra_time_per_day,
+                &cumulated_extra_time_per_day,
+            );
+            let sheet_id = opt.sheet_id.as_ref().unwrap();
+            let uploaded = GoogleCredentials::from_file(opt.gcp_credential_file.as_ref().unwrap())
+                .and_then(|credentials| upload_to_sheets(&report, sheet_id, &credentials));
+            if let Err(error) = uploaded {
+                eprintln!("Could not upload the report to Google Sheets: {}", error);
+                std::process::exit(1);
+            }
+        }
+
+        if opt.notion_page {
+            let report = ExtraTimeReport::new(
+                query_start,
+                query_end,
+                &all_days,
+                &total_work_duration_per_day,
+                &extra_time_per_day,
+                &cumulated_extra_time_per_day,
+            );
+            match create_notion_page(
+                &report,
+                opt.parent_page.as_ref().unwrap(),
+                opt.notion_token.as_ref().unwrap(),
+            ) {
+                Ok(url) => report!("Notion page created : {}", url),
+                Err(error) => {
+                    eprintln!("Could not create the Notion page: {}", error);
+                    std::process::exit(1);
+                }
+            }
+        }
+
+        if opt.airtable {
+            let report = ExtraTimeReport::new(
+                query_start,
+                query_end,
+                &all_days,
+                &total_work_duration_per_day,
+                &extra_time_per_day,
+                &cumulated_extra_time_per_day,
+            );
+            if let Err(error) = sync_to_airtable(
+                &report,
+                opt.base_id.as_ref().unwrap(),
+                opt.table.as_ref().unwrap(),
+                opt.airtable_token.as_ref().unwrap(),
+            ) {
+                eprintln!("Could not sync the report to Airtable: {}", error);
+                std::process::exit(1);
+            }
+        }
+
+        if let Some(path) = &opt.export_entries {
+            let mut entries: Vec<Entry> = all_days
+                .iter()
+                .flat_map(|day| entries_per_day.get(day).unwrap().clone())
+                .collect();
+            entries.sort_by_key(|entry| entry.start);
+            let written = match &opt.entry_format {
+                Some(format) => write_formatted_entries(&entries, format, &project_names, path),
+                None => {
+                    let client_names = fetch_client_names(&toggl, &projects);
+                    write_entries_csv(&entries, &project_names, &client_names, path)
+                }
+            };
+            if let Err(error) = written {
+                eprintln!("Could not export the entries: {}", error);
+                std::process::exit(1);
+            }
+        }
+
+        if opt.show_worst_week || opt.show_best_week {
+            let weeks = compute_weekly_summaries(
+                &all_days,
+                &total_work_duration_per_day,
+                &extra_time_per_day,
+            );
+            if !weeks.is_empty() {
+                let (worst, best) = find_extreme_weeks(&weeks);
+                if opt.show_worst_week {
+                    report!(
+                        "Worst week: {} to {}, worked {}, extra time {}",
+                        worst.start,
+                        worst.end,
+                        display_duration(worst.worked_seconds),
+                        display_balance(worst.extra_seconds)
+                    );
+                }
+                if opt.show_best_week {
+                    report!(
+                        "Best week: {} to {}, worked {}, extra time {}",
+                        best.start,
+                        best.end,
+                        display_duration(best.worked_seconds),
+                        display_balance(best.extra_seconds)
+                    );
+                }
+            }
+        }
+
+        if (opt.show_longest_day || opt.show_shortest_day) && !all_days.is_empty() {
+            let (shortest, longest) = find_extreme_days(&all_days, &total_work_duration_per_day);
+            if opt.show_longest_day {
+                report!(
+                    "Longest day: {}, worked {}",
+                    longest,
+                    display_duration(*total_work_duration_per_day.get(&longest).unwrap())
+                );
+            }
+            if opt.show_shortest_day {
+                report!(
+                    "Shortest day: {}, worked {}",
+                    shortest,
+                    display_duration(*total_work_duration_per_day.get(&shortest).unwrap())
+                );
+            }
+        }
+
+        let all_dates: Vec<NaiveDate> = all_days
+            .iter()
+            .map(|day| NaiveDate::parse_from_str(day, "%Y-%m-%d").unwrap())
+            .collect();
+
+        if opt.show_work_sessions {
+            for day in &all_days {
+                let sessions = detect_sessions(
+                    entries_per_day.get(day).unwrap(),
+                    opt.session_gap_minutes * 60,
+                );
+                let sessions: Vec<String> = sessions
+                    .iter()
+                    .enumerate()
+                    .map(|(i, session)| {
+                        format!(
+                            "Session {}: {}–{} ({}, {} {})",
+                            i + 1,
+                            session.start.format("%H:%M"),
+                            session.end.format("%H:%M"),
+                            display_hours_minutes(session.total_duration_secs),
+                            session.entries.len(),
+                            if session.entries.len() > 1 {
+                                "entries"
+                            } else {
+                                "entry"
+                            }
+                        )
+                    })
+                    .collect();
+                report!("{}: {}", day, sessions.join(", "));
+            }
+        }
+
+        if opt.show_entry_gaps {
+            for day in &all_days {
+                let entries = entries_per_day.get(day).unwrap();
+                report!(
+                    "{}: {} uncovered",
+                    day,
+                    display_hours_minutes(uncovered_time(entries, opt.session_gap_minutes * 60))
+                );
+            }
+        }
+
+        if opt.session_count_stats && !all_days.is_empty() {
+            let session_counts: Vec<(&String, usize)> = all_days
+                .iter()
+                .map(|day| {
+                    let entries = entries_per_day.get(day).unwrap();
+                    (
+                        day,
+                        detect_sessions(entries, opt.session_gap_minutes * 60).len(),
+                    )
+                })
+                .collect();
+            let average = session_counts.iter().map(|(_, count)| count).sum::<usize>() as f64
+                / session_counts.len() as f64;
+            let (max_day, max_count) = session_counts
+                .iter()
+                .max_by_key(|(_, count)| *count)
+                .unwrap();
+            let (min_day, min_count) = session_counts
+                .iter()
+                .min_by_key(|(_, count)| *count)
+                .unwrap();
+            let plural = |count: usize| if count > 1 { "sessions" } else { "session" };
+            report!(
+                "Average sessions per day: {}, Max: {} {} on {}, Min: {} {} on {}",
+                locale.format_decimal(average, 1),
+                max_count,
+                plural(*max_count),
+                max_day,
+                min_count,
+                plural(*min_count),
+                min_day
+            );
+        }
+
+        if opt.show_break_duration {
+            for day in &all_days {
+                let sessions = detect_sessions(
+                    entries_per_day.get(day).unwrap(),
+                    opt.session_gap_minutes * 60,
+                );
+                let gaps = sessions.len().saturating_sub(1);
+                report!(
+                    "Breaks on {}: {} total ({} {})",
+                    day,
+                    display_hours_minutes(total_break_time(&sessions)),
+                    gaps,
+                    if gaps == 1 { "gap" } else { "gaps" }
+                );
+            }
+        }
+
+        if opt.lunch_break_detection {
+            for day in &all_days {
+                match detect_lunch_break(entries_per_day.get(day).unwrap()) {
+                    Some((start, end, duration)) => report!(
+                        "Lunch on {}: {}–{} ({})",
+                        day,
+                        start.format("%H:%M"),
+                        end.format("%H:%M"),
+                        display_hours_minutes(duration)
+                    ),
+                    None => report!(
+                        "Warning: no lunch break found on {} between 11:00 and 14:00",
+                        day
+                    ),
+                }
+            }
+        }
+
+        if opt.show_start_times && !entries_per_day.is_empty() {
+            let stats = start_time_stats(&entries_per_day);
+            report!(
+                "Average start time: {}, Earliest: {} on {}, Latest: {} on {}",
+                stats.average.format("%H:%M"),
+                stats.earliest.1.format("%H:%M"),
+                stats.earliest.0,
+                stats.latest.1.format("%H:%M"),
+                stats.latest.0
+            );
+        }
+
+        if opt.show_end_times && !entries_per_day.is_empty() {
+            let stats = end_time_stats(&entries_per_day);
+            report!(
+                "Average end time: {}, Earliest finish: {} on {}, Latest: {} on {}",
+                stats.average.format("%H:%M"),
+                stats.earliest.1.format("%H:%M"),
+                stats.earliest.0,
+                stats.latest.1.format("%H:%M"),
+                stats.latest.0
+            );
+        }
+
+        if opt.overtime_days_count {
+            let (overtime_days, under_time_days, on_target_days) =
+                count_overtime_days(&extra_time_per_day);
+            let total_days = extra_time_per_day.len();
+            report!(
+                "Overtime days: {}/{}, Under-time days: {}/{}, On-target days: {}/{}",
+                overtime_days,
+                total_days,
+                under_time_days,
+                total_days,
+                on_target_days,
+                total_days
+            );
+        }
+
+        if opt.show_consecutive_deficit {
+            for streak in find_deficit_streaks(&all_dates, &extra_time_per_day, 3) {
+                report!(
+                    "Consecutive under-time streak: {} days from {} to {} (total deficit: {})",
+                    streak.length,
+                    streak.start,
+                    streak.end,
+                    display_extra(streak.total_extra_seconds)
+                );
+            }
+        }
+
+        if opt.show_consecutive_surplus {
+            for streak in find_surplus_streaks(&all_dates, &extra_time_per_day, 3) {
+                report!(
+                    "Consecutive overtime streak: {} days from {} to {} (total surplus: {})",
+                    streak.length,
+                    streak.start,
+                    streak.end,
+                    display_extra(streak.total_extra_seconds)
+                );
+            }
+        }
+
+        if opt.cumulative_chart && !all_dates.is_empty() {
+            report!(
+                "{}",
+                render_cumulative_chart(
+                    &all_dates,
+                    &cumulated_extra_time_per_day,
+                    terminal_width() as u16,
+                    CHART_HEIGHT,
+                    &locale
+                )
+            );
+        }
+
+        if opt.dual_chart && !all_dates.is_empty() {
+            report!(
+                "{}",
+                render_dual_chart(
+                    &all_dates,
+                    &extra_time_per_day,
+                    &cumulated_extra_time_per_day,
+                    terminal_width() as u16,
+                    CHART_HEIGHT * 2,
+                    &locale
+                )
+            );
+        }
+
+        if opt.interactive_chart && !all_dates.is_empty() {
+            run_interactive_chart(
+                &all_dates,
+                &extra_time_per_day,
+                &entries_per_day,
+                &display_hours_minutes,
+                &display_extra,
+                &locale,
+            )
+            .expect("Could not display the interactive chart");
+        }
+
+        if opt.check_duplicates {
+            for (day, duration, count) in find_duplicate_durations(&tasks_per_day) {
+                report!(
+                    "Day {}: possible duplicate entries ({}s appears {} times)",
+                    day,
+                    duration,
+                    count
+                );
+            }
+        }
+
+        if opt.show_daily_rank {
+            let ranks = daily_ranks(&extra_time_per_day);
+            let mut ranked_days: Vec<&String> = all_days.iter().collect();
+            ranked_days.sort_by_key(|day| (ranks.get(*day).unwrap(), *day));
+            report!("Daily rank:");
+            for day in ranked_days {
+                report!(
+                    "  {:>3}. {}  {}",
+                    ranks.get(day).unwrap(),
+                    day,
+                    display_balance(*extra_time_per_day.get(day).unwrap())
+                );
+            }
+        }
+
+        if opt.group_by_weekday {
+            let averages = group_by_weekday(&all_dates, &total_work_duration_per_day);
+            let weekdays: Vec<String> = (0..7)
+                .map(|index| Weekday::try_from(index as u8).unwrap())
+                .filter(|weekday| all_dates.iter().any(|date| date.weekday() == *weekday))
+                .map(|weekday| {
+                    format!(
+                        "{} average: {}",
+                        weekday_name(weekday),
+                        display_hours_minutes(averages[weekday as usize])
+                    )
+                })
+                .collect();
+            report!("{}", weekdays.join(" | "));
+        }
+
+        if opt.seasonality && !all_dates.is_empty() {
+            let averages = group_by_weekday(&all_dates, &extra_time_per_day);
+            let extra_per_weekday: Vec<(Weekday, i64)> = (0..7)
+                .map(|index| Weekday::try_from(index as u8).unwrap())
+                .filter(|weekday| all_dates.iter().any(|date| date.weekday() == *weekday))
+                .map(|weekday| (weekday, averages[weekday as usize]))
+                .collect();
+            let summary: Vec<String> = extra_per_weekday
+                .iter()
+                .map(|(weekday, extra)| {
+                    format!(
+                        "{}s average {}",
+                        weekday_name(*weekday),
+                        display_extra(*extra)
+                    )
+                })
+                .collect();
+            report!("{}", summary.join(", "));
+            let (highest_weekday, highest) = extra_per_weekday
+                .iter()
+                .max_by_key(|(_, extra)| *extra)
+                .unwrap();
+            let (lowest_weekday, lowest) = extra_per_weekday
+                .iter()
+                .min_by_key(|(_, extra)| *extra)
+                .unwrap();
+            report!(
+                "Most extra time on {}s ({}), least on {}s ({})",
+                weekday_name(*highest_weekday),
+                display_extra(*highest),
+                weekday_name(*lowest_weekday),
+                display_extra(*lowest)
+            );
+        }
+
+        if opt.burnout_index {
+            let weeks = compute_weekly_summaries(
+                &all_days,
+                &total_work_duration_per_day,
+                &extra_time_per_day,
+            );
+            let daily_extra: Vec<i64> = all_days
+                .iter()
+                .map(|day| *extra_time_per_day.get(day).unwrap())
+                .collect();
+            let index = burnout_index(
+                &positive_week_streaks(&weeks),
+                &daily_extra,
+                (opt.weekly_overtime_cap_hours * 3600.0) as i64,
+            );
+            report!("Burnout index: {}", locale.format_decimal(index, 2));
+            if index > 1.0 {
+                report!(
+                    "Warning: your extra time has been high for several weeks in a row, consider slowing down."
+                );
+            }
+        }
+
+        if opt.correlation && all_dates.len() >= 2 {
+            let x: Vec<f64> = all_dates.iter().map(|date| date.day() as f64).collect();
+            let y: Vec<f64> = all_days
+                .iter()
+                .map(|day| *extra_time_per_day.get(day).unwrap() as f64)
+                .collect();
+            let r = pearson_correlation(&x, &y);
+            report!(
+                "Correlation between day-of-month and extra time: r = {} ({})",
+                locale.format_decimal(r, 2),
+                describe_correlation(r)
+            );
+        }
+
+        if let Some(n_bins) = opt.histogram {
+            let daily_extra: Vec<i64> = all_days
+                .iter()
+                .map(|day| *extra_time_per_day.get(day).unwrap())
+                .collect();
+            let bins = histogram(&daily_extra, n_bins);
+            if !bins.is_empty() {
+                report!("{}", render_histogram(&bins, 8, &display_extra));
+            }
+        }
+
+        if opt.confidence_interval && all_days.len() >= 2 {
+            let daily_extra: Vec<i64> = all_days
+                .iter()
+                .map(|day| *extra_time_per_day.get(day).unwrap())
+                .collect();
+            let (low, high) = confidence_interval_95(&daily_extra);
+            report!(
+                "95% CI for daily extra time: [{}, {}]",
+                display_extra(low.round() as i64),
+                display_extra(high.round() as i64)
+            );
+        }
+
+        let trend_x: Vec<f64> = all_dates
+            .iter()
+            .map(|date| (*date - all_dates[0]).num_days() as f64)
+            .collect();
+        let trend_y: Vec<f64> = all_days
+            .iter()
+            .map(|day| *cumulated_extra_time_per_day.get(day).unwrap() as f64)
+            .collect();
+
+        if opt.trend && all_dates.len() >= 2 {
+            let (x, y) = (&trend_x, &trend_y);
+            let (slope, intercept) = linear_regression(x, y);
+            let minutes_per_day = slope / 60.0;
+            report!(
+                "Overtime is growing at {}{}min/day on average over the period.",
+                if minutes_per_day >= 0.0 { "+" } else { "" },
+                locale.format_decimal(minutes_per_day, 1)
+            );
+            if debug {
+                report!(
+                    "R² of the trend: {}",
+                    locale.format_decimal(r_squared(x, y, slope, intercept), 3)
+                );
+            }
+        }
+
+        if let Some(forecast_days) = opt.forecast_days
+            && forecast_days > 0
+            && all_dates.len() >= 2
+        {
+            let (slope, intercept) = linear_regression(&trend_x, &trend_y);
+            let standard_error = slope_standard_error(&trend_x, &trend_y, slope, intercept);
+            let forecast_date = nth_working_day_after(query_end, forecast_days);
+            let days_ahead = (forecast_date - query_end).num_days() as f64;
+            let projected = total_extra_time_worked as f64 + slope * days_ahead;
+            let margin = 1.96 * standard_error * days_ahead;
+            report!(
+                "Projected balance in {} working days ({}): {} (if trend continues), 95% band [{}, {}]",
+                forecast_days,
+                forecast_date,
+                display_extra(projected.round() as i64),
+                display_extra((projected - margin).round() as i64),
+                display_extra((projected + margin).round() as i64)
+            );
+        }
+
+        if include_today && opt.show_today_entry_count {
+            let today = current_time.date_naive().format("%Y-%m-%d").to_string();
+            let today_tasks = tasks_per_day.get(&today).cloned().unwrap_or_default();
+            report!(
+                "Today so far: {} entries, {} logged",
+                today_tasks.len(),
+                display_duration(today_tasks.iter().sum())
+            );
+        }
+
+        if opt.show_rate_per_month {
+            let months = months_in_range(query_start, query_end);
+            if months > 0.0 {
+                let per_month = (total_extra_time_worked as f64 / months).round() as i64;
+                report!(
+                    "Average overtime per month: {}",
+                    display_as(per_month, true, format_hours_minutes)
+                );
+                report!(
+                    "Projected annual overtime: {}",
+                    display_as(per_month * 12, true, format_hours_minutes)
+                );
+            }
+        }
+
+        if opt.show_rate_per_week {
+            let weeks = weeks_in_range(query_start, query_end);
+            if weeks > 0.0 {
+                report!(
+                    "Average overtime per week: {}",
+                    display_as(
+                        (total_extra_time_worked as f64 / weeks).round() as i64,
+                        true,
+                        format_hours_minutes,
+                    )
+                );
+            }
+        }
 
-        let hours = extra_time_worked.num_hours();
-        let minutes = extra_time_worked.num_minutes() - (hours * 60);
-        let seconds = extra_time_worked.num_seconds() - (hours * 60 * 60) - (minutes * 60);
         if debug {
-            println!(
+            report!(
                 "Extra time worked in seconds: {}",
-                extra_time_worked.num_seconds()
+                locale.format_integer(total_extra_time_worked)
+            );
+        }
+        if opt.alert_on_zero && total_extra_time_worked == 0 {
+            let message = "Perfect balance! You have worked exactly your contracted hours.";
+            let terminal = if opt.ndjson {
+                std::io::stderr().is_terminal()
+            } else {
+                std::io::stdout().is_terminal()
+            };
+            if terminal {
+                report!("\x1b[1;32m{}\x1b[0m", message);
+            } else {
+                report!("{}", message);
+            }
+        } else if !opt.no_summary && !opt.ndjson {
+            let decoration = if !emoji {
+                ""
+            } else if total_extra_time_worked > 0 {
+                " 🎉"
+            } else if total_extra_time_worked < LARGE_NEGATIVE_BALANCE_IN_SECONDS {
+                " 😰"
+            } else {
+                ""
+            };
+            report!(
+                "Total extra time worked: {}{}",
+                display_balance(total_extra_time_worked),
+                decoration
             );
         }
-        println!(
-            "Total extra time worked: {}h{}min{}sec",
-            hours, minutes, seconds
+
+        // Shown even without the summary line
+        let mut balance_lines: Vec<String> = Vec::new();
+        let report = ExtraTimeReport::new(
+            query_start,
+            query_end,
+            &all_days,
+            &total_work_duration_per_day,
+            &extra_time_per_day,
+            &cumulated_extra_time_per_day,
         );
+        if opt.include_weekends {
+            balance_lines.push(format!(
+                "Weekday extra time worked: {}",
+                display_balance(report.weekday_extra_seconds)
+            ));
+            balance_lines.push(format!(
+                "Weekend extra time worked: {}",
+                display_balance(report.weekend_extra_seconds)
+            ));
+        }
+        if let Some(hourly_rate) = opt.hourly_rate {
+            let earnings = |seconds: i64, rate: f64| seconds as f64 / 3600.0 * rate;
+            if opt.include_weekends {
+                let weekday_earnings = earnings(report.weekday_extra_seconds, hourly_rate);
+                let weekend_earnings = earnings(
+                    report.weekend_extra_seconds,
+                    hourly_rate * opt.weekend_rate_multiplier.unwrap_or(1.0),
+                );
+                balance_lines.push(format!(
+                    "Weekday extra time earnings: {}",
+                    locale.format_decimal(weekday_earnings, 2)
+                ));
+                balance_lines.push(format!(
+                    "Weekend extra time earnings: {}",
+                    locale.format_decimal(weekend_earnings, 2)
+                ));
+                balance_lines.push(format!(
+                    "Total extra time earnings: {}",
+                    locale.format_decimal(weekday_earnings + weekend_earnings, 2)
+                ));
+            } else {
+                balance_lines.push(format!(
+                    "Total extra time earnings: {}",
+                    locale.format_decimal(earnings(report.total, hourly_rate), 2)
+                ));
+            }
+        }
+        for line in balance_lines {
+            report!("{}", line);
+        }
+
+        if opt.api_call_count {
+            toggl.print_diagnostic(&format!("Total API calls made: {}", toggl.call_count()));
+        }
+
+        if opt.negative_is_error && total_extra_time_worked < 0 {
+            eprintln!(
+                "Balance is negative: {}",
+                display_as(total_extra_time_worked, true, format_hours_minutes)
+            );
+            std::process::exit(2);
+        }
+        if opt.positive_is_error && total_extra_time_worked > 0 {
+            eprintln!(
+                "Balance is positive: {}. Please schedule time off.",
+                display_extra(total_extra_time_worked)
+            );
+            std::process::exit(3);
+        }
+        if let Some(max_balance_hours) = opt.max_balance_hours
+            && total_extra_time_worked as f64 > max_balance_hours * 3600.0
+        {
+            eprintln!(
+                "Balance {} is above the maximum of {}h",
+                display_extra(total_extra_time_worked),
+                max_balance_hours
+            );
+            std::process::exit(3);
+        }
+        if let Some(min_balance_hours) = opt.min_balance_hours
+            && (total_extra_time_worked as f64) < min_balance_hours * 3600.0
+        {
+            eprintln!(
+                "Balance {} is below the minimum of {}h",
+                display_extra(total_extra_time_worked),
+                min_balance_hours
+            );
+            std::process::exit(2);
+        }
     } else {
-        println!("You need to specify a token");
+        report!("You need to specify a token");
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn date(year: i32, month: u32, day: u32) -> NaiveDate {
+        NaiveDate::from_ymd_opt(year, month, day).unwrap()
+    }
+
+    #[test]
+    fn quarter_bounds_of_calendar_quarters() {
+        assert_eq!(
+            quarter_bounds(2024, 1, 1),
+            Some((date(2024, 1, 1), date(2024, 3, 31)))
+        );
+        assert_eq!(
+            quarter_bounds(2024, 2, 1),
+            Some((date(2024, 4, 1), date(2024, 6, 30)))
+        );
+        assert_eq!(
+            quarter_bounds(2024, 3, 1),
+            Some((date(2024, 7, 1), date(2024, 9, 30)))
+        );
+        assert_eq!(
+            quarter_bounds(2024, 4, 1),
+            Some((date(2024, 10, 1), date(2024, 12, 31)))
+        );
+    }
+
+    #[test]
+    fn quarter_bounds_of_april_fiscal_year() {
+        assert_eq!(
+            quarter_bounds(2024, 1, 4),
+            Some((date(2024, 4, 1), date(2024, 6, 30)))
+        );
+        assert_eq!(
+            quarter_bounds(2024, 4, 4),
+            Some((date(2025, 1, 1), date(2025, 3, 31)))
+        );
+    }
+
+    #[test]
+    fn quarter_bounds_rejects_invalid_quarters_and_years() {
+        assert_eq!(quarter_bounds(2024, 0, 1), None);
+        assert_eq!(quarter_bounds(2024, 5, 1), None);
+        assert_eq!(quarter_bounds(300000, 1, 1), None);
+    }
+
+    #[test]
+    fn last_week_range_follows_week_start() {
+        // 2024-01-10 is a Wednesday
+        assert_eq!(
+            last_week_range(date(2024, 1, 10), Weekday::Mon),
+            (date(2024, 1, 1), date(2024, 1, 7))
+        );
+        assert_eq!(
+            last_week_range(date(2024, 1, 10), Weekday::Sun),
+            (date(2023, 12, 31), date(2024, 1, 6))
+        );
+        assert_eq!(
+            last_week_range(date(2024, 1, 8), Weekday::Mon),
+            (date(2024, 1, 1), date(2024, 1, 7))
+        );
+    }
+
+    #[test]
+    fn last_month_range_of_january_is_previous_december() {
+        assert_eq!(
+            last_month_range(date(2024, 1, 15)),
+            (date(2023, 12, 1), date(2023, 12, 31))
+        );
+        assert_eq!(
+            last_month_range(date(2024, 3, 1)),
+            (date(2024, 2, 1), date(2024, 2, 29))
+        );
+    }
+
+    #[test]
+    fn previous_quarter_of_calendar_and_fiscal_years() {
+        assert_eq!(
+            previous_quarter(date(2024, 5, 15), 1),
+            (date(2024, 1, 1), date(2024, 3, 31))
+        );
+        assert_eq!(
+            previous_quarter(date(2024, 2, 10), 1),
+            (date(2023, 10, 1), date(2023, 12, 31))
+        );
+        assert_eq!(
+            previous_quarter(date(2024, 4, 1), 4),
+            (date(2024, 1, 1), date(2024, 3, 31))
+        );
+        assert_eq!(
+            previous_quarter(date(2024, 3, 31), 4),
+            (date(2023, 10, 1), date(2023, 12, 31))
+        );
+    }
+
+    #[test]
+    fn parse_timezone_offset_rejects_signed_parts() {
+        assert_eq!(
+            parse_timezone_offset("+05:30"),
+            Ok(FixedOffset::east_opt(5 * 3600 + 30 * 60).unwrap())
+        );
+        assert_eq!(
+            parse_timezone_offset("-03:00"),
+            Ok(FixedOffset::west_opt(3 * 3600).unwrap())
+        );
+        assert!(parse_timezone_offset("+05:-30").is_err());
+        assert!(parse_timezone_offset("+-05:30").is_err());
+        assert!(parse_timezone_offset("++05:30").is_err());
+        assert!(parse_timezone_offset("+05:60").is_err());
     }
 }